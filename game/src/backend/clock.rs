@@ -0,0 +1,104 @@
+//! Contains the `GameClock`, which decides how many ticks the game advances in a frame.
+use crate::backend::constants::{DESIRED_FPS, MAX_TICKS_PER_UPDATE};
+use std::time::Duration;
+
+/// How fast the game time passes compared to the real time
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum GameSpeed {
+    #[default]
+    Normal,
+    Double,
+    Quadruple,
+}
+
+impl GameSpeed {
+    /// Returns how many ticks pass in the time of one tick at normal speed
+    pub(crate) fn factor(self) -> u32 {
+        match self {
+            GameSpeed::Normal => 1,
+            GameSpeed::Double => 2,
+            GameSpeed::Quadruple => 4,
+        }
+    }
+
+    /// Returns the next faster speed, the fastest speed is followed by the normal speed
+    pub(crate) fn next(self) -> Self {
+        match self {
+            GameSpeed::Normal => GameSpeed::Double,
+            GameSpeed::Double => GameSpeed::Quadruple,
+            GameSpeed::Quadruple => GameSpeed::Normal,
+        }
+    }
+}
+
+/// The clock of a running game. It runs `DESIRED_FPS` ticks per second of real time at normal
+/// speed, independent of the frame rate. If the game falls behind, it catches up at most
+/// `MAX_TICKS_PER_UPDATE` ticks per frame and drops the rest, so a slow frame does not freeze it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct GameClock {
+    pub(crate) paused: bool,
+    pub(crate) speed: GameSpeed,
+    /// The real time which has passed but was not turned into ticks yet
+    accumulator: Duration,
+}
+
+impl GameClock {
+    /// Returns the real time one tick takes at normal speed
+    fn tick_duration() -> Duration {
+        Duration::from_secs(1) / DESIRED_FPS
+    }
+
+    /// Advances the clock by the real time of a frame
+    /// # Arguments
+    /// * `elapsed` - The real time since the last frame
+    /// # Returns
+    /// * `u32` - The number of ticks the game has to run in this frame
+    pub(crate) fn advance(&mut self, elapsed: Duration) -> u32 {
+        if self.paused {
+            return 0;
+        }
+        self.accumulator += elapsed * self.speed.factor();
+        let tick_duration = Self::tick_duration();
+        let mut ticks = 0;
+        while self.accumulator >= tick_duration {
+            self.accumulator -= tick_duration;
+            ticks += 1;
+        }
+        if ticks > MAX_TICKS_PER_UPDATE {
+            self.accumulator = Duration::ZERO;
+            ticks = MAX_TICKS_PER_UPDATE;
+        }
+        ticks
+    }
+
+    /// Pauses or resumes the game time
+    pub(crate) fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.accumulator = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ticks_follow_real_time() {
+        let mut clock = GameClock::default();
+        let frame = Duration::from_secs(1) / 120;
+        let ticks: u32 = (0..120).map(|_| clock.advance(frame)).sum();
+        assert_eq!(ticks, DESIRED_FPS);
+        clock.speed = GameSpeed::Normal.next().next();
+        assert_eq!(clock.advance(Duration::from_secs(1) / DESIRED_FPS), 4);
+        clock.toggle_pause();
+        assert_eq!(clock.advance(Duration::from_secs(1)), 0);
+    }
+
+    #[test]
+    fn test_catch_up_is_limited() {
+        let mut clock = GameClock::default();
+        assert_eq!(clock.advance(Duration::from_secs(5)), MAX_TICKS_PER_UPDATE);
+        // The ticks which were dropped are not caught up later
+        assert_eq!(clock.advance(Duration::ZERO), 0);
+    }
+}
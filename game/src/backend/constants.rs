@@ -2,13 +2,7 @@
 //! Author: ["Benedikt Brandmaier", "Maximilian Floto", "Marion Hinkel", "Sander Stella", "Philipp Wolf"]
 
 use crate::backend::rlcolor::RLColor;
-use crate::game_core::player::gen_inventory;
-use crate::game_core::resources::Resources;
-use crate::languages::german::MACHINE_NAMES;
-use crate::machines::machine::{Machine, State};
-use crate::machines::trade::Trade;
-use ggez::graphics::{Color, Rect};
-use std::string::ToString;
+use ggez::graphics::Color;
 
 /// Contains the screen resolution of the game.
 /// The game is designed to be played in 1920x1080.
@@ -18,12 +12,107 @@ pub const SCREEN_RESOLUTION: (f32, f32) = (1920., 1080.);
 /// Contains the desired FPS of the game-loop.
 pub(crate) const DESIRED_FPS: u32 = 60;
 
-/// Contains the coordinates map border( x-right, y-bottom, x-left, y-top)
-pub const MAP_BORDER: [usize; 4] = [1780, 860, 270, 220];
+/// Contains the path of the machine catalog, which describes all machines of the game.
+pub(crate) const MACHINE_CATALOG_PATH: &str = "assets/data/machines.yaml";
+
+/// Contains the path of the item registry, which describes all items of the game.
+pub(crate) const ITEM_REGISTRY_PATH: &str = "assets/data/items.yaml";
+
+/// Contains the path of the objective catalog, which describes the campaign of the game.
+pub(crate) const OBJECTIVE_CATALOG_PATH: &str = "assets/data/objectives.yaml";
+
+/// Contains the path of the event catalog, which describes all events and how often they occur.
+pub(crate) const EVENT_CATALOG_PATH: &str = "assets/data/events.yaml";
+
+/// Contains the path of the settings file, which is kept when a new game is started.
+pub(crate) const SETTINGS_PATH: &str = "./settings.yaml";
+
+/// Contains the codes of all languages in `assets/lang`, the first one is the default language.
+pub const LANGUAGES: [&str; 2] = ["de", "en"];
+
+/// Contains the path of the level used when a save does not name its level.
+pub const DEFAULT_LEVEL_PATH: &str = "assets/levels/habitat.yaml";
+
+/// Contains the folder the autosave and the milestone save are written to.
+pub(crate) const SAVES_PATH: &str = "./saves";
+
+/// Contains the file name of the autosave, which is loaded when the player resumes the game.
+pub(crate) const AUTOSAVE_FILE: &str = "autosave.yaml";
+
+/// Contains the number of older autosaves which are kept in case the newest one is damaged.
+pub(crate) const AUTOSAVE_BACKUPS: usize = 3;
+
+/// Contains the default number of in-game minutes between two periodic autosaves.
+pub(crate) const AUTOSAVE_INTERVAL: u32 = 2;
+
+/// Contains the autosave intervals the player can choose in the settings, 0 turns autosaves off.
+pub(crate) const AUTOSAVE_INTERVALS: [u32; 4] = [1, AUTOSAVE_INTERVAL, 5, 0];
+
+/// Contains the file name of the milestone save, which is loaded when the player dies.
+pub(crate) const MILESTONE_FILE: &str = "milestone.yaml";
+
+/// Contains the folder the save slots are written to.
+pub(crate) const SAVE_SLOTS_PATH: &str = "./saves/slots";
+
+/// Contains the number of save slots.
+pub(crate) const SAVE_SLOTS: usize = 5;
+
+/// Contains the version of the save format, older saves are upgraded when they are loaded.
+/// Bumping it needs a migration and a fixture of the new version in `test-saves`.
+pub(crate) const SAVE_VERSION: u32 = 4;
+
+/// Contains the folder recordings of games are written to.
+pub(crate) const RECORDINGS_PATH: &str = "./recordings";
+
+/// Contains the version of the recording format, recordings of other versions can not be replayed.
+pub(crate) const RECORDING_VERSION: u32 = 11;
+
+/// Contains the durability of a machine which is as good as new.
+pub(crate) const MAX_DURABILITY: u16 = 1000;
+
+/// Contains the durability below which a running machine can break down.
+pub(crate) const DURABILITY_THRESHOLD: u16 = 300;
+
+/// Contains the number of outcomes of the roll deciding whether a worn machine breaks down in a
+/// second, a machine without any durability left breaks down in `DURABILITY_THRESHOLD` of them.
+pub(crate) const FAILURE_ROLL: usize = 3000;
+
+/// Contains the percentage of the paid items the player gets back when cancelling a trade, unless
+/// the trade sets its own refund.
+pub(crate) const DEFAULT_REFUND: u8 = 50;
+
+/// Contains the amount of items the output buffer of a machine holds, unless the machine sets its
+/// own capacity.
+pub(crate) const DEFAULT_OUTPUT_CAPACITY: u16 = 5;
+
+/// Contains the total weight of the items the player can carry.
+pub const MAX_CARRY_WEIGHT: i32 = 60;
+
+/// Contains the share of the difference in pressure and oxygen of two rooms which flows through
+/// an open door between them per tick, as one part of `DOOR_FLOW`.
+pub(crate) const DOOR_FLOW: i32 = 16;
+
+/// Contains the energy per tick the battery of the power grid delivers at most while it is charged.
+/// It carries the load at the start of the game: the oxygen generator (30), the workshop (15), the
+/// station itself (10) and the hole which leaks from the start (5). Any further consumer needs the
+/// generator.
+pub(crate) const BATTERY_OUTPUT: i16 = 60;
+
+/// Contains the position of the power grid panel.
+pub(crate) const GRID_POSITION: (f32, f32) = (60., 160.);
+
+/// Contains the position, the width and the height of a row of the interaction menu.
+pub(crate) const MENU_AREA: (f32, f32, f32, f32) = (1220., 160., 640., 30.);
+
+/// Contains the position of the inventory.
+pub(crate) const INVENTORY_POSITION: (f32, f32) = (990., 955.);
 
 /// Contains the position of the resource bars.
 pub(crate) const RESOURCE_POSITION: [f32; 3] = [316.0, 639.0, 1373.0];
 
+/// Contains the message ids of the resource names shown next to the resource bars.
+pub(crate) const RESOURCE_NAMES: [&str; 3] = ["hud.oxygen", "hud.energy", "hud.life"];
+
 /// Contains the color used for the resource bars.
 pub(crate) const COLORS: [Color; 3] = [RLColor::BLUE, RLColor::GOLD, RLColor::DARK_RED];
 
@@ -39,245 +128,8 @@ pub const MOVEMENT_SPEED: usize = 10;
 /// Contains the position of the time.
 pub(crate) const TIME_POSITION: (f32, f32) = (1205., 960.);
 
-/// Change rate fot the event Sandsturm
-pub(crate) const SANDSTURM_CR: Resources<i16> = Resources {
-    oxygen: 10,
-    energy: 0,
-    life: 0,
-};
-
-#[allow(clippy::too_many_lines)]
-/// Generates all machines with all their name, position, trades and resources.
-/// # Returns
-/// A Vector of `Machine`s
-pub(crate) fn gen_all_machines() -> Vec<Machine> {
-    vec![
-        // Oxygen machine
-        Machine::new_by_const((
-            MACHINE_NAMES[0].to_string(),
-            Rect {
-                x: 280.0,
-                y: 230.0,
-                w: 350.0,
-                h: 182.0,
-            },
-            vec![
-                Trade::new(
-                    "repair_Oxygen".to_string(),
-                    100,
-                    State::Broken,
-                    State::Idle,
-                    false,
-                    gen_inventory(2, 0, 0),
-                ),
-                Trade::new(
-                    "start_Oxygen".to_string(),
-                    0,
-                    State::Idle,
-                    State::Running,
-                    true,
-                    gen_inventory(0, 0, 0),
-                ),
-                Trade::new(
-                    "stop_Oxygen".to_string(),
-                    0,
-                    State::Running,
-                    State::Idle,
-                    true,
-                    gen_inventory(0, 0, 0),
-                ),
-            ],
-            Resources {
-                oxygen: 30,
-                energy: -30,
-                life: 0,
-            },
-        )),
-        // Electricity machine
-        Machine::new_by_const((
-            MACHINE_NAMES[1].to_string(),
-            Rect {
-                x: 282.0,
-                y: 752.0,
-                w: 194.0,
-                h: 189.0,
-            },
-            vec![
-                Trade::new(
-                    "fueling_Stromgenerator".to_string(),
-                    700,
-                    State::Broken,
-                    State::Running,
-                    true,
-                    gen_inventory(0, 1, 0),
-                ),
-                Trade::new(
-                    "start_Stromgenerator".to_string(),
-                    1,
-                    State::Idle,
-                    State::Running,
-                    true,
-                    gen_inventory(0, 0, 0),
-                ),
-                Trade::new(
-                    "stop_Stromgenerator".to_string(),
-                    0,
-                    State::Running,
-                    State::Idle,
-                    true,
-                    gen_inventory(0, 0, 0),
-                ),
-            ],
-            Resources {
-                oxygen: -5,
-                energy: 200,
-                life: 0,
-            },
-        )),
-        // Worker machine
-        Machine::new_by_const((
-            MACHINE_NAMES[2].to_string(),
-            Rect {
-                x: 1000.0,
-                y: 780.0,
-                w: 300.0,
-                h: 150.0,
-            },
-            vec![
-                Trade::new(
-                    "repair_werkermaschine".to_string(),
-                    100,
-                    State::Broken,
-                    State::Idle,
-                    false,
-                    gen_inventory(0, 0, 1),
-                ),
-                Trade::new(
-                    "produce_superglue".to_string(),
-                    120,
-                    State::Idle,
-                    State::Running,
-                    true,
-                    gen_inventory(-1, 0, 0),
-                ),
-            ],
-            Resources {
-                oxygen: 0,
-                energy: -15,
-                life: 0,
-            },
-        )),
-        // 3d Printer machine
-        Machine::new_by_const((
-            MACHINE_NAMES[3].to_string(),
-            Rect {
-                x: 930.0,
-                y: 230.0,
-                w: 200.0,
-                h: 148.0,
-            },
-            vec![
-                Trade::new(
-                    "repair_3d_printer".to_string(),
-                    300,
-                    State::Broken,
-                    State::Idle,
-                    false,
-                    gen_inventory(2, 0, 0),
-                ),
-                Trade::new(
-                    "produce_3d_teil".to_string(),
-                    200,
-                    State::Idle,
-                    State::Running,
-                    true,
-                    gen_inventory(2, 0, -1),
-                ),
-            ],
-            Resources {
-                oxygen: 0,
-                energy: -25,
-                life: 0,
-            },
-        )),
-        // Communication module
-        Machine::new_by_const((
-            MACHINE_NAMES[4].to_string(),
-            Rect {
-                x: 1640.0,
-                y: 320.0,
-                w: 175.0,
-                h: 477.0,
-            },
-            vec![
-                Trade::new(
-                    "Kommunikationsmodul_reparieren".to_string(),
-                    400,
-                    State::Broken,
-                    State::Idle,
-                    false,
-                    gen_inventory(5, 0, 3),
-                ),
-                Trade::new(
-                    "Notfall_signal_absetzen".to_string(),
-                    1000,
-                    State::Idle,
-                    State::Running,
-                    true,
-                    gen_inventory(1, 0, 1),
-                ),
-            ],
-            Resources {
-                oxygen: 0,
-                energy: -30,
-                life: 0,
-            },
-        )),
-        // First hole
-        Machine::new_by_const((
-            MACHINE_NAMES[5].to_string(),
-            Rect {
-                x: 780.0,
-                y: 230.0,
-                w: 32.0,
-                h: 18.0,
-            },
-            vec![Trade::new(
-                "repair_Loch".to_string(),
-                100,
-                State::Running,
-                State::Idle,
-                false,
-                gen_inventory(2, 0, 0),
-            )],
-            Resources {
-                oxygen: -15,
-                energy: -5,
-                life: 0,
-            },
-        )),
-        // Second hole
-        Machine::new_by_const((
-            MACHINE_NAMES[6].to_string(),
-            Rect {
-                x: 680.0,
-                y: 900.0,
-                w: 32.0,
-                h: 18.0,
-            },
-            vec![Trade::new(
-                "repair_Loch".to_string(),
-                100,
-                State::Running,
-                State::Idle,
-                false,
-                gen_inventory(2, 0, 0),
-            )],
-            Resources {
-                oxygen: -15,
-                energy: -5,
-                life: 0,
-            },
-        )),
-    ]
-}
+/// Contains the ticks after which a game played by a bot without a window is stopped (one hour)
+pub const BOT_MAX_TICKS: u32 = 60 * 60 * DESIRED_FPS;
+
+/// Contains the most ticks a game runs in one frame to catch up, the remaining time is dropped
+pub(crate) const MAX_TICKS_PER_UPDATE: u32 = 16;
@@ -2,6 +2,9 @@
 //! Author: ["Benedikt Brandmaier", "Philipp Wolf" ]
 use crate::backend::gamestate::GameCommand;
 use crate::backend::screen::StackCommand;
+use crate::main_menu::mainmenu::Message;
+use crate::main_menu::settingsmenu::SettingsMessage;
+use crate::main_menu::slotscreen::SlotMessage;
 use ggez::GameError;
 use std::io;
 use std::sync::mpsc::SendError;
@@ -21,6 +24,8 @@ pub enum RLError {
     IO(io::Error),
     /// Errors where senders/receivers were not intialized properly
     InitError(String),
+    /// Errors caused by invalid entries in one of the game data files
+    InvalidData(String),
 }
 
 impl From<GameError> for RLError {
@@ -52,6 +57,15 @@ fn create_io_error(message: &str, value: impl std::fmt::Display) -> RLError {
         format!("{message}: {value}"),
     ))
 }
+/// Creates an error for an invalid entry in a game data file
+/// # Arguments
+/// * `file` - The data file containing the invalid entry
+/// * `field` - The path of the invalid field inside the file
+/// * `message` - Describes what is wrong with the field
+pub fn create_data_error(file: &str, field: &str, message: impl std::fmt::Display) -> RLError {
+    error!("Invalid data in {} at {}: {}", file, field, message);
+    RLError::InvalidData(format!("{file}: {field}: {message}"))
+}
 /// Macro for converting a `SendError` to an `RLError`
 /// Author: ["Benedikt Brandmaier"]
 #[macro_export]
@@ -66,4 +80,10 @@ macro_rules! convert_senderror {
         )*
     }
 }
-convert_senderror!(GameCommand, StackCommand);
+convert_senderror!(
+    GameCommand,
+    StackCommand,
+    Message,
+    SlotMessage,
+    SettingsMessage
+);
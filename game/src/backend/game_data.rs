@@ -0,0 +1,75 @@
+//! Contains the `GameData`, which bundles all data files a game is played with.
+use crate::backend::constants::{
+    EVENT_CATALOG_PATH, ITEM_REGISTRY_PATH, MACHINE_CATALOG_PATH, OBJECTIVE_CATALOG_PATH,
+};
+use crate::backend::level::Level;
+use crate::game_core::event_catalog::EventCatalog;
+use crate::game_core::item::ItemRegistry;
+use crate::game_core::objective::ObjectiveCatalog;
+use crate::machines::catalog::load_machine_catalog;
+use crate::machines::machine::Machine;
+use crate::RLResult;
+
+/// All data files needed to run a game, loading them does not need a window or a GPU
+#[derive(Debug, Clone)]
+pub struct GameData {
+    pub item_registry: ItemRegistry,
+    /// The machines of the machine catalog, which are placed by the level when a new game starts
+    pub machine_catalog: Vec<Machine>,
+    pub objective_catalog: ObjectiveCatalog,
+    pub event_catalog: EventCatalog,
+    pub level: Level,
+}
+
+impl GameData {
+    /// Loads all data files from the assets folder.
+    /// # Arguments
+    /// * `level_path` - The path of the level file
+    /// # Returns
+    /// * `RLResult<GameData>` - The loaded data or an error describing the invalid entry
+    pub fn load(level_path: &str) -> RLResult<Self> {
+        let item_registry = ItemRegistry::load(ITEM_REGISTRY_PATH)?;
+        Ok(Self {
+            machine_catalog: load_machine_catalog(MACHINE_CATALOG_PATH, &item_registry)?,
+            objective_catalog: ObjectiveCatalog::load(OBJECTIVE_CATALOG_PATH, &item_registry)?,
+            event_catalog: EventCatalog::load(EVENT_CATALOG_PATH)?,
+            level: Level::load(level_path)?,
+            item_registry,
+        })
+    }
+
+    /// Returns the data files of the repository, which are compiled in so that the tests of the
+    /// game and of `rl-save` do not depend on the working directory.
+    pub fn bundled() -> Self {
+        let item_registry = ItemRegistry::parse(
+            "items.yaml",
+            include_str!("../../../assets/data/items.yaml"),
+        )
+        .unwrap();
+        Self {
+            machine_catalog: crate::machines::catalog::parse_machine_catalog(
+                "machines.yaml",
+                include_str!("../../../assets/data/machines.yaml"),
+                &item_registry,
+            )
+            .unwrap(),
+            objective_catalog: ObjectiveCatalog::parse(
+                "objectives.yaml",
+                include_str!("../../../assets/data/objectives.yaml"),
+                &item_registry,
+            )
+            .unwrap(),
+            event_catalog: EventCatalog::parse(
+                "events.yaml",
+                include_str!("../../../assets/data/events.yaml"),
+            )
+            .unwrap(),
+            level: Level::parse(
+                "habitat.yaml",
+                include_str!("../../../assets/levels/habitat.yaml"),
+            )
+            .unwrap(),
+            item_registry,
+        }
+    }
+}
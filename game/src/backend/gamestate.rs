@@ -1,44 +1,75 @@
 //! Contains the game logic, updates the game and draws the current board
 //! Author: ["Benedikt Brandmaier", "Maximilian Floto", "Marion Hinkel", "Leo Schnüll", "Sander Stella", "Philipp Wolf"]
+use crate::backend::clock::{GameClock, GameSpeed};
 use crate::backend::constants::{
-    COLORS, DESIRED_FPS, MAP_BORDER, RESOURCE_POSITION, TIME_POSITION,
+    AUTOSAVE_BACKUPS, AUTOSAVE_FILE, COLORS, DEFAULT_LEVEL_PATH, DESIRED_FPS, FAILURE_ROLL,
+    INVENTORY_POSITION, MAX_CARRY_WEIGHT, MILESTONE_FILE, RESOURCE_NAMES, RESOURCE_POSITION,
+    SAVES_PATH, TIME_POSITION,
 };
+use crate::backend::game_data::GameData;
+use crate::backend::level::Level;
+use crate::backend::movement::Input;
+use crate::backend::options::Options;
+use crate::backend::recording::{state_checksum, Recorder, Replayer};
 use crate::backend::rlcolor::RLColor;
+use crate::backend::rng::GameRng;
+use crate::backend::save_file;
+use crate::backend::save_slots::SlotFile;
 use crate::backend::screen::{Popup, StackCommand};
-use crate::backend::utils::get_scale;
+use crate::backend::settings::Settings;
+use crate::backend::utils::{format_play_time, get_scale};
 use crate::backend::utils::{get_draw_params, is_colliding};
 use crate::backend::{error::RLError, screen::Screen};
+use crate::bots::controller::{Controller, Observation};
+use crate::bots::scripted_agent::ScriptedAgent;
+use crate::game_core::atmosphere::Atmosphere;
 use crate::game_core::event::Event;
+use crate::game_core::event_catalog::EventCatalog;
+use crate::game_core::floor_item::FloorItem;
 use crate::game_core::infoscreen::DeathReason::{Both, Energy, Oxygen};
 use crate::game_core::infoscreen::InfoScreen;
-use crate::game_core::item::Item;
+use crate::game_core::item::ItemRegistry;
+use crate::game_core::objective::{Objective, ObjectiveCatalog, ObjectiveProgress};
 use crate::game_core::player::Player;
 use crate::game_core::resources::Resources;
-use crate::languages::german::{
-    FIRST_MILESTONE_HANDBOOK_TEXT, MACHINE_NAMES, RESOURCE_NAME, SECOND_MILESTONE_HANDBOOK_TEXT,
-    TIME_NAME,
-};
 use crate::machines::machine::Machine;
-use crate::machines::machine::State::Broken;
-use crate::{draw, RLResult};
+use crate::machines::power_grid::PowerGrid;
+use crate::main_menu::mainmenu::Message;
+use crate::main_menu::pausemenu::PauseMenu;
+use crate::main_menu::slotscreen::SlotScreen;
+use crate::{draw, tr, RLResult};
+use ggez::event::MouseButton;
 use ggez::glam::Vec2;
 use ggez::graphics::{Canvas, Image, TextFragment};
 use ggez::graphics::{DrawMode, Mesh, Rect};
+use ggez::winit::event::VirtualKeyCode;
 use ggez::{graphics, Context};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::fs::read_dir;
-use std::sync::mpsc::{channel, Receiver, Sender};
-use tracing::info;
+use std::io;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::thread::JoinHandle;
+use tracing::{info, warn};
 
 /// Contains all Commands used to between the machines and the gamestate.
 /// Author: ["Benedikt Brandmaier", "Sander Stella"]
 pub enum GameCommand {
-    AddItems(Vec<(Item, i32)>),
+    AddItems(Vec<(String, i32)>),
     ResourceChange(Resources<i16>),
-    Milestone,
-    Winning,
+    /// Sent by a machine when the timer of one of its trades has run out
+    TradeCompleted(String),
+}
+
+/// The outcome of a finished game
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player completed an objective which wins the game
+    Won,
+    /// The player ran out of life
+    Died,
 }
 
 /// This is the game state. It contains all the data that is needed to run the game.
@@ -48,9 +79,43 @@ pub struct GameState {
     /// Contains the current player position, resources(air, energy, life) and the inventory and their change rates
     pub player: Player,
     /// Contains the event generator and the current events
-    pub(crate) events: Vec<Event>,
+    pub events: Vec<Event>,
+    #[serde(default)]
+    /// Contains the time (in ticks) at which each event is off its cooldown again,
+    /// sorted so that the same game is always saved the same way
+    pub(crate) event_cooldowns: BTreeMap<String, u32>,
+    #[serde(default)]
+    /// Makes all random decisions of the game, saves without one get a random seed
+    pub(crate) rng: GameRng,
+    #[serde(skip)]
+    /// Contains all items of the game, the inventory only stores their ids
+    pub item_registry: ItemRegistry,
+    #[serde(default)]
+    /// Contains the progress of the player in the objective graph
+    pub objectives: ObjectiveProgress,
+    #[serde(skip)]
+    /// Contains all objectives of the campaign
+    pub(crate) objective_catalog: ObjectiveCatalog,
+    #[serde(skip)]
+    /// Contains all events which can be generated and their weights
+    pub(crate) event_catalog: EventCatalog,
+    #[serde(default)]
+    /// Contains the path of the level the game is played in, `None` stands for the default level
+    pub level_path: Option<String>,
+    #[serde(skip)]
+    /// Contains the background, the walkable area and the machine placements of the level
+    pub(crate) level: Level,
     /// Contains the machines and their current state
     pub machines: Vec<Machine>,
+    #[serde(default)]
+    /// Contains the priorities of the consumers on the power grid
+    pub(crate) grid: PowerGrid,
+    #[serde(default)]
+    /// Contains the air of every room and which doors are open
+    pub(crate) atmosphere: Atmosphere,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Contains the items lying on the floor
+    pub floor_items: Vec<FloorItem>,
     #[serde(skip)]
     /// Contains all the images that are needed to draw the game on the canvas
     assets: HashMap<String, Image>,
@@ -65,6 +130,39 @@ pub struct GameState {
     pub(crate) sender: Option<Sender<GameCommand>>,
     /// Defines if the handbook is currently open
     pub handbook_invisible: bool,
+    #[serde(skip)]
+    /// Defines if the power grid panel is currently open
+    pub(crate) grid_visible: bool,
+    #[serde(skip)]
+    /// Contains the index of the machine whose interaction menu is open
+    pub(crate) interaction_menu: Option<usize>,
+    #[serde(skip)]
+    /// Set for games run by a `Simulation` and replays, which are never saved
+    pub disable_saves: bool,
+    #[serde(skip)]
+    /// Contains the command line options, which are handed on to the next game
+    pub(crate) options: Options,
+    #[serde(skip)]
+    /// Records the input of the player if the game is recorded
+    recorder: Option<Recorder>,
+    #[serde(skip)]
+    /// Provides the input instead of the keyboard while a recording is replayed
+    pub replayer: Option<Replayer>,
+    #[serde(skip)]
+    /// Provides the input instead of the keyboard if the game is played by a bot
+    pub(crate) controller: Option<Box<dyn Controller>>,
+    #[serde(skip)]
+    /// Decides how many ticks run in a frame, can pause and fast-forward the game
+    pub(crate) clock: GameClock,
+    #[serde(skip)]
+    /// Receives the action chosen in the `PauseMenu` while it is open
+    menu_receiver: Option<Receiver<Message>>,
+    #[serde(skip)]
+    /// The in-game minutes between two periodic autosaves, taken from the `Settings`
+    autosave_interval: u32,
+    #[serde(skip)]
+    /// Writes the last autosave in the background, so that saving does not interrupt the game
+    background_save: Option<JoinHandle<RLResult>>,
 }
 
 impl GameState {
@@ -87,24 +185,39 @@ impl GameState {
         ))
     }
 
-    /// Creates a new game state at the beginning of the game and after every loading.
-    /// It loads all the assets and creates the areas of the machines.
+    /// Creates the game state of a new game, which does not need a window or a GPU.
+    /// The machines are placed as described by the level and the player starts at its spawn point.
+    /// # Arguments
+    /// * `data` - The data files the game is played with
+    /// * `seed` - The seed of the random number generator
     /// # Returns
-    /// * `RLResult<GameState>`: The new game state initialized in a `RLResult` to handle setup errors
+    /// * `RLResult<GameState>`: The new game state in a `RLResult` to handle invalid levels
     /// Author: ["Benedikt Brandmaier", "Philipp Wolf"]
-    pub fn new(ctx: &mut Context) -> RLResult<Self> {
-        info!("Creating new gamestate");
-        let (sender, receiver) = channel();
+    pub(crate) fn new_game(data: GameData, seed: u64) -> RLResult<Self> {
+        info!("Creating new gamestate with seed {}", seed);
         let mut result = GameState {
-            sender: Some(sender),
-            receiver: Some(receiver),
-            ..Default::default()
+            machines: data.level.place_machines(&data.machine_catalog)?,
+            rng: GameRng::new(seed),
+            ..GameState::default()
         };
-        result.init(ctx)?;
+        result.set_data(data);
+        result.player.inventory = result.item_registry.starting_inventory();
+        result.player.position = result.level.spawn;
         Ok(result)
     }
+    /// Advances the game by one tick, this is the same for the game and a `Simulation`.
+    /// # Arguments
+    /// * `input` - The input of the player in this tick
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the tick
+    pub(crate) fn step(&mut self, input: Input) -> RLResult {
+        self.tick()?;
+        self.move_player(input)?;
+        Event::update_events(self)?;
+        Ok(())
+    }
     /// Gets called every tick in the update fn to update the internal game logic.
-    /// It updates the player resources, checks on the current milestone if the player has reached a new one
+    /// It updates the player resources, checks if the player has completed an objective
     /// and checks if the player has died.
     /// # Returns
     /// * `RLResult`: A `RLResult` to validate the success of the tick function
@@ -118,62 +231,68 @@ impl GameState {
             .zip(self.player.resources_change.into_iter())
             .map(|(a, b)| a.saturating_add_signed(b))
             .collect::<Resources<_>>();
+        self.update_atmosphere();
         self.player.time += 1;
         // Everything inside will only be checked every 15 ticks
 
         // Check if the player is dead
         if let Some(empty_resource) = Resources::get_death_reason(self.player.resources) {
             match empty_resource {
-                Both => {
-                    self.player.resources_change.life = -60;
-                    self.machines.iter_mut().for_each(Machine::no_energy);
-                }
+                Both => self.player.resources_change.life = -60,
                 Oxygen => self.player.resources_change.life = -50,
-                Energy => {
-                    self.player.resources_change.life = -10;
-                    self.machines.iter_mut().for_each(Machine::no_energy);
-                }
+                Energy => self.player.resources_change.life = -10,
             };
             if self.player.resources.life == 0 {
-                let gamestate = GameState::load(true).unwrap_or_default();
-                gamestate.save(false)?;
+                if !self.disable_saves {
+                    self.finish_background_save()?;
+                    let (mut gamestate, _) = GameState::load(true).unwrap_or_default();
+                    gamestate.save(false)?;
+                }
                 let cloned_sender = self.get_screen_sender()?.clone();
+                let options = self.options.clone();
                 self.get_screen_sender()?.send(StackCommand::Push(Box::new(
-                    InfoScreen::new_deathscreen(empty_resource, cloned_sender),
+                    InfoScreen::new_deathscreen(empty_resource, cloned_sender, options),
                 )))?;
             };
         } else if self.player.resources_change.life < 0 {
             self.player.resources_change.life = 0;
         }
 
+        self.check_objectives()?;
+
         // process received GameCommands
         if let Ok(msg) = self.get_receiver()?.try_recv() {
             match msg {
                 GameCommand::ResourceChange(new_rs) => {
                     self.player.resources_change = self.player.resources_change + new_rs;
                 }
-                GameCommand::AddItems(items) => {
-                    for (item, amount) in &items {
-                        self.player.add_item(item, *amount);
+                GameCommand::AddItems(items) => self.add_items(&items),
+                GameCommand::TradeCompleted(name) => {
+                    let hints = self
+                        .objectives
+                        .active
+                        .iter()
+                        .filter_map(|(id, _)| self.objective_catalog.get(id)?.hints.get(&name))
+                        .cloned()
+                        .collect::<Vec<String>>();
+                    for hint in hints {
+                        let popup = Popup::new(RLColor::GREEN, tr!(&hint), 5);
+                        self.get_screen_sender()?.send(StackCommand::Popup(popup))?;
                     }
+                    self.objectives
+                        .completed_trades
+                        .insert(name, self.player.time);
+                    self.save_in_background(SAVES_PATH, false)?;
                 }
-                GameCommand::Milestone => {
-                    self.get_current_milestone()?;
-                }
-                GameCommand::Winning => match self.player.milestone {
-                    1 => {
-                        let sender = self.get_screen_sender()?;
-                        let popup = Popup::new(RLColor::GREEN, "Die Nachricht kann nicht gesendet werden solange das System nicht wiederhergestellt ist".to_string(), 5);
-                        sender.send(StackCommand::Popup(popup))?;
-                    }
-                    2 => {
-                        self.player.milestone += 1;
-                        self.get_current_milestone()?;
-                    }
-                    _ => {}
-                },
             };
         }
+        // The grid is balanced once per second, the battery covers the load in between
+        if self.player.time % DESIRED_FPS == 0 {
+            self.brownout()?;
+        }
+        if self.is_autosave_due() {
+            self.save_in_background(SAVES_PATH, false)?;
+        }
 
         // Regenerate life if applicable
         self.player
@@ -181,6 +300,9 @@ impl GameState {
         for machine in &mut self.machines {
             machine.tick()?;
         }
+        if self.player.time % DESIRED_FPS == 0 {
+            self.wear_machines()?;
+        }
 
         Ok(())
     }
@@ -209,7 +331,7 @@ impl GameState {
                 draw!(canvas, &mesh, scale);
                 let text = graphics::Text::new(format!(
                     "{}: {:.1}",
-                    RESOURCE_NAME[i],
+                    tr!(RESOURCE_NAMES[i]),
                     (resource as f32 / u16::MAX as f32) * 100.0
                 ));
                 draw!(
@@ -224,6 +346,7 @@ impl GameState {
         Ok(())
     }
     /// Draws the handbook while pressing the H key
+    /// The handbook shows the text of the first active objective which has one.
     /// # Arguments
     /// * `canvas`: The canvas to draw on
     /// * `ctx`: The `Context` of the game
@@ -234,15 +357,24 @@ impl GameState {
         let scale = get_scale(ctx);
         let image = self.assets.get("Handbook.png").unwrap();
         draw!(canvas, image, Vec2::new(700.0, 300.0), scale);
-        match self.player.milestone {
-            1 => {
-                self.draw_handbook_text(canvas, scale, &FIRST_MILESTONE_HANDBOOK_TEXT);
-            }
-
-            2 => {
-                self.draw_handbook_text(canvas, scale, &SECOND_MILESTONE_HANDBOOK_TEXT);
-            }
-            _ => {}
+        if let Some(objective) = self
+            .objectives
+            .active
+            .iter()
+            .filter_map(|(id, _)| self.objective_catalog.get(id))
+            .find(|objective| !objective.handbook.is_empty())
+        {
+            let mut lines = objective
+                .handbook
+                .iter()
+                .flat_map(|id| tr!(id).lines().map(str::to_string).collect::<Vec<String>>())
+                .collect::<Vec<String>>();
+            lines.extend([
+                String::new(),
+                String::new(),
+                format!("           {}", tr!("handbook.close")),
+            ]);
+            self.draw_handbook_text(canvas, scale, &lines);
         }
         Ok(())
     }
@@ -250,28 +382,26 @@ impl GameState {
     /// # Arguments
     /// * `canvas`: The canvas to draw on
     /// * `scale`: The scale of the canvas
-    /// * `handbook_text`: The text to draw on the screen
+    /// * `handbook_text`: The translated lines to draw on the screen
     /// # Returns
     /// * `RLResult`: A `RLResult` to validate the success of the function
     /// Author: ["Marion Hinkel"]
-    pub fn draw_handbook_text(&self, canvas: &mut Canvas, scale: Vec2, handbook_text: &[&str]) {
-        handbook_text
-            .iter()
-            .enumerate()
-            .for_each(|(i, const_text)| {
-                let mut text =
-                    graphics::Text::new(TextFragment::new(*const_text).color(RLColor::BLACK));
-                text.set_scale(28.0);
-                draw!(
-                    canvas,
-                    &text,
-                    Vec2::new(800.0, 400.0 + (i * 30) as f32),
-                    scale
-                );
-            });
+    pub fn draw_handbook_text(&self, canvas: &mut Canvas, scale: Vec2, handbook_text: &[String]) {
+        handbook_text.iter().enumerate().for_each(|(i, line)| {
+            let mut text =
+                graphics::Text::new(TextFragment::new(line.as_str()).color(RLColor::BLACK));
+            text.set_scale(28.0);
+            draw!(
+                canvas,
+                &text,
+                Vec2::new(800.0, 400.0 + (i * 30) as f32),
+                scale
+            );
+        });
     }
 
     /// Iterates trough the inventory and draws the amount of every item in the inventory.
+    /// If the mouse hovers over an item, its name and info text are drawn above the inventory.
     /// # Arguments
     /// * `canvas` - The current canvas to draw on
     /// * `ctx` - The current game context
@@ -279,46 +409,98 @@ impl GameState {
     /// * `RLResult` - validates if the drawing was successful
     /// Author: ["Marion Hinkel"]
     fn draw_items(&self, canvas: &mut Canvas, ctx: &mut Context) -> RLResult {
-        self.player
-            .inventory
-            .clone()
-            .into_iter()
-            .enumerate()
-            .map(|(i, (item, amount))| {
-                let img = self.assets.get(item.img.as_str()).unwrap();
-                let position = (990., 955.);
-                let scale = get_scale(ctx);
-                draw!(
-                    canvas,
-                    img,
-                    Vec2::new(position.0 + (i * 65) as f32, position.1),
-                    scale
+        let position = INVENTORY_POSITION;
+        let scale = get_scale(ctx);
+        let mouse = ctx.mouse.position();
+        let weight = self.item_registry.weight(&self.player.inventory);
+        let color = if weight >= MAX_CARRY_WEIGHT {
+            RLColor::STATUS_RED
+        } else {
+            RLColor::BLACK
+        };
+        let text = graphics::Text::new(
+            TextFragment::new(format!(
+                "{}: {weight}/{MAX_CARRY_WEIGHT}",
+                tr!("hud.weight")
+            ))
+            .color(color)
+            .scale(18.),
+        );
+        draw!(
+            canvas,
+            &text,
+            Vec2::new(position.0 - 120., position.1),
+            scale
+        );
+        for (i, (id, amount)) in self.player.inventory.iter().enumerate() {
+            let Some(item) = self.item_registry.get(id) else {
+                continue;
+            };
+            let img = self.get_asset(item.img.as_str())?;
+            let item_position = Vec2::new(position.0 + (i * 65) as f32, position.1);
+            draw!(canvas, img, item_position, scale);
+            draw!(
+                canvas,
+                &graphics::Text::new(format!("{amount}")),
+                Vec2::new(position.0 + (i * 63) as f32, position.1),
+                scale
+            );
+            let area = Rect::new(
+                item_position.x * scale.x,
+                item_position.y * scale.y,
+                img.width() as f32 * scale.x,
+                img.height() as f32 * scale.y,
+            );
+            if area.contains(mouse) {
+                let mut text = graphics::Text::new(
+                    TextFragment::new(format!("{}: {}", tr!(&item.name), tr!(&item.info_text)))
+                        .color(RLColor::BLACK),
                 );
+                text.set_scale(18.0);
                 draw!(
                     canvas,
-                    &graphics::Text::new(format!("{amount}")),
-                    Vec2::new(position.0 + (i * 63) as f32, position.1),
+                    &text,
+                    Vec2::new(position.0, position.1 - 25.),
                     scale
                 );
-            })
-            .for_each(drop);
+            }
+        }
         Ok(())
     }
 
+    /// Returns the inventory slot at a position
+    /// # Arguments
+    /// * `position` - The position on the unscaled screen, e.g. of the mouse
+    /// # Returns
+    /// * `Option<usize>` - The place of the item in the inventory, `None` if there is none
+    pub(crate) fn inventory_slot_at(&self, position: (f32, f32)) -> Option<usize> {
+        (0..self.player.inventory.len()).find(|i| {
+            Rect::new(
+                INVENTORY_POSITION.0 + (i * 65) as f32,
+                INVENTORY_POSITION.1,
+                60.,
+                40.,
+            )
+            .contains(Vec2::from(position))
+        })
+    }
+
     /// Draws the current time on the screen
     /// # Arguments
     /// * `canvas` - The current canvas to draw on
     /// * `scale` - The current scale of the canvas
     /// Author: ["Philipp Wolf"]
     pub(crate) fn draw_time(&self, canvas: &mut Canvas, scale: Vec2) {
-        let time = self.player.time / DESIRED_FPS;
-        let time_text = format!(
-            "{}: {}h {}m {}s",
-            TIME_NAME[0],
-            time / 3600,
-            time / 60,
-            time % 60
+        let mut time_text = format!(
+            "{}: {}",
+            tr!("hud.time"),
+            format_play_time(self.player.time)
         );
+        if self.clock.paused {
+            time_text = format!("{time_text} ({})", tr!("hud.paused"));
+        } else if self.clock.speed != GameSpeed::Normal {
+            time_text = format!("{time_text} (x{})", self.clock.speed.factor());
+        }
         let mut text = graphics::Text::new(TextFragment::new(time_text).color(RLColor::BLACK));
         text.set_scale(18.0);
         draw!(
@@ -328,14 +510,38 @@ impl GameState {
             scale
         );
     }
-    /// Loads the assets. Has to be called before drawing the game.
+    /// Loads the data files and the assets of a loaded game. Has to be called before drawing the game.
     /// # Returns
-    /// * `RLResult` - Returns an error if the assets could not be loaded.
+    /// * `RLResult` - Returns an error if the data files or the assets could not be loaded.
     /// Author: ["Benedikt Brandmaier", "Sander Stella"]
-    pub(crate) fn init(&mut self, ctx: &mut Context) -> RLResult {
+    pub fn init(&mut self, ctx: &mut Context) -> RLResult {
+        let level_path = self.level_path.as_deref().unwrap_or(DEFAULT_LEVEL_PATH);
+        self.set_data(GameData::load(level_path)?);
+        self.load_assets(ctx)
+    }
+    /// Sets the data files the game is played with and creates the channel used by the machines.
+    /// # Arguments
+    /// * `data` - The data files the game is played with
+    pub(crate) fn set_data(&mut self, data: GameData) {
+        self.item_registry = data.item_registry;
+        self.objective_catalog = data.objective_catalog;
+        self.event_catalog = data.event_catalog;
+        self.level = data.level;
+        let (sender, receiver) = channel();
+        self.sender = Some(sender);
+        self.receiver = Some(receiver);
+    }
+    /// Loads the images of the assets folder. Has to be called before drawing the game.
+    /// # Returns
+    /// * `RLResult` - Returns an error if the assets could not be loaded.
+    pub(crate) fn load_assets(&mut self, ctx: &mut Context) -> RLResult {
         info!("Loading assets");
         read_dir("assets")?.for_each(|file| {
             let file = file.unwrap();
+            // Only images are loaded here, data files are loaded by their own loaders
+            if file.path().extension().map_or(true, |ext| ext != "png") {
+                return;
+            }
             let bytes = fs::read(file.path()).unwrap();
             let name = file.file_name().into_string().unwrap();
             self.assets
@@ -345,15 +551,22 @@ impl GameState {
         if self.assets.is_empty() {
             return Err(RLError::AssetError("Could not find assets!".to_string()));
         }
-        let (sender, receiver) = channel();
-        self.sender = Some(sender);
-        self.receiver = Some(receiver);
         Ok(())
     }
-    /// Initializes the machines by loading the assets for all existing machines
+    /// Initializes the machines by connecting them to the game and loading the assets for all existing machines
     /// Checks if the machine has one asset if it does not change or three assets for the different states
+    /// Headless games have no assets, so their machines have no sprites
     /// Author: ["Benedikt Brandmaier", "Sander Stella"]
     pub(crate) fn init_all_machines(&mut self) {
+        for machine in &mut self.machines {
+            machine.init(
+                self.sender.clone().unwrap(),
+                self.screen_sender.clone().unwrap(),
+            );
+        }
+        if self.assets.is_empty() {
+            return;
+        }
         let machine_assets: Vec<Vec<Image>> = self
             .machines
             .iter()
@@ -383,75 +596,118 @@ impl GameState {
         self.machines
             .iter_mut()
             .zip(machine_assets)
-            .for_each(|(m, a)| {
-                m.init(
-                    a.as_slice(),
-                    self.sender.clone().unwrap(),
-                    self.screen_sender.clone().unwrap(),
-                );
-            });
+            .for_each(|(m, a)| m.set_sprite(a.as_slice()));
     }
 
     /// Saves the active game state to a file. The boolean value "milestone" determines whether this is a milestone or an autosave.
-    /// If the file already exists, it will be overwritten.
+    /// The file is replaced atomically, the previous autosaves are kept as backups.
     /// # Arguments
     /// * `milestone` - Boolean value that determines whether this is a milestone save or an autosave.
     /// # Returns
     /// * `RLResult` - validates if the save was successful
     /// Author: ["Philipp Wolf"]
-    pub(crate) fn save(&self, milestone: bool) -> RLResult {
-        let save_data = serde_yaml::to_string(self)?;
+    pub(crate) fn save(&mut self, milestone: bool) -> RLResult {
+        if self.disable_saves {
+            return Ok(());
+        }
+        // A save still written in the background must not replace this one afterwards
+        self.finish_background_save()?;
+        Self::write_save(SAVES_PATH, &save_file::serialize(self)?, milestone)
+    }
+    /// Saves the active game state as autosave without waiting for the file to be written.
+    /// The game is serialized right away, only writing the file happens on another thread.
+    /// # Arguments
+    /// * `dir` - The folder the saves are written to
+    /// * `milestone` - Whether the game is saved as milestone too
+    /// # Returns
+    /// * `RLResult` - Fails if the game could not be serialized or the previous save failed
+    fn save_in_background(&mut self, dir: &str, milestone: bool) -> RLResult {
+        if self.disable_saves {
+            return Ok(());
+        }
+        self.finish_background_save()?;
+        let save_data = save_file::serialize(self)?;
+        let dir = dir.to_string();
+        self.background_save = Some(thread::spawn(move || {
+            if milestone {
+                Self::write_save(&dir, &save_data, true)?;
+            }
+            Self::write_save(&dir, &save_data, false)
+        }));
+        Ok(())
+    }
+    /// Returns whether the periodic autosave is due in the current tick
+    fn is_autosave_due(&self) -> bool {
+        self.autosave_interval > 0
+            && self.player.time % (self.autosave_interval * 60 * DESIRED_FPS) == 0
+    }
+    /// Waits until the save written in the background, if any, is finished
+    /// # Returns
+    /// * `RLResult` - The result of writing the save
+    pub(crate) fn finish_background_save(&mut self) -> RLResult {
+        match self.background_save.take() {
+            Some(handle) => handle.join().unwrap_or_else(|_| {
+                Err(RLError::IO(io::Error::new(
+                    io::ErrorKind::Other,
+                    "The thread writing the save panicked",
+                )))
+            }),
+            None => Ok(()),
+        }
+    }
+    /// Writes the content of a save file as milestone or as autosave
+    /// # Arguments
+    /// * `dir` - The folder the save is written to
+    /// * `save_data` - The content of the save file
+    /// * `milestone` - Whether the file is written as milestone or as autosave
+    fn write_save(dir: &str, save_data: &str, milestone: bool) -> RLResult {
         // Create the folder if it doesn't exist
-        fs::create_dir_all("./saves")?;
+        fs::create_dir_all(dir)?;
         if milestone {
-            fs::write("./saves/milestone.yaml", save_data)?;
+            save_file::write_atomic(&format!("{dir}/{MILESTONE_FILE}"), save_data)?;
             info!("Saved game state as milestone");
         } else {
-            fs::write("./saves/autosave.yaml", save_data)?;
+            let path = format!("{dir}/{AUTOSAVE_FILE}");
+            save_file::rotate_backups(&path, AUTOSAVE_BACKUPS)?;
+            save_file::write_atomic(&path, save_data)?;
             info!("Saved game state as autosave");
         }
         Ok(())
     }
     /// Loads a game state from a file. The boolean value "milestone" determines whether this is a milestone or an autosave.
-    /// If the file doesn't exist, it will return a default game state.
+    /// Saves of older versions are upgraded to the current version. If the autosave is damaged,
+    /// the newest valid backup is loaded instead.
     /// # Arguments
     /// * `milestone` - Whether to load the milestone or the autosave
     /// # Returns
-    /// * `RLResult<Gamestate>` containing the loaded game state or a default game state if the file doesn't exist.
+    /// * `RLResult<(GameState, bool)>` containing the loaded game state and whether a backup was loaded, or an error if no valid save exists.
     /// Author: ["Philipp Wolf"]
-    pub fn load(milestone: bool) -> RLResult<GameState> {
-        let save_data = if milestone {
+    pub fn load(milestone: bool) -> RLResult<(GameState, bool)> {
+        if milestone {
             info!("Loading milestone...");
-            fs::read_to_string("./saves/milestone.yaml")
+            save_file::read_with_backups(&format!("{SAVES_PATH}/{MILESTONE_FILE}"), 0)
         } else {
             info!("Loading autosave...");
-            fs::read_to_string("./saves/autosave.yaml")
-        }?;
-        let game_state: GameState = serde_yaml::from_str(&save_data)?;
-
-        Ok(game_state)
-    }
-    /// Returns the area the player needs to stand in to interact with a machine
-    /// # Returns
-    /// * `Option<&mut Machine>` - The machines the player can interact with if one exists or None
-    ///  Author: ["Benedikt Brandmaier"]
-    pub(crate) fn get_interactable(&mut self) -> Option<&mut Machine> {
-        self.machines
-            .iter_mut()
-            .find(|machine| machine.is_interactable(self.player.position))
+            save_file::read_with_backups(&format!("{SAVES_PATH}/{AUTOSAVE_FILE}"), AUTOSAVE_BACKUPS)
+        }
     }
-
-    /// Returns if the player would collide with a border if they moved in the given direction
+    /// Adds items to the inventory of the player, negative amounts are removed.
+    /// The items the player can not carry are put on the floor.
     /// # Arguments
-    /// * `next_player_pos` - The direction the player wants to move
-    /// Author: ["Marion Hinkel"]
-    fn border_collision_detection(next_player_pos: (usize, usize)) -> bool {
-        next_player_pos.0 >= MAP_BORDER[0] // Right border
-            || next_player_pos.1 >= MAP_BORDER[1] // Bottom border
-            || next_player_pos.0 <= MAP_BORDER[2] // Left border
-            || next_player_pos.1 <= MAP_BORDER[3] // Top border
+    /// * `items` - The ids and amounts of the items
+    pub fn add_items(&mut self, items: &[(String, i32)]) {
+        for (id, amount) in items {
+            let Some(item) = self.item_registry.get(id) else {
+                warn!("Tried to add unknown item {}", id);
+                continue;
+            };
+            let carried = (*amount).min(self.player.room_for(item, &self.item_registry));
+            let overflow = self.player.add_item(item, carried);
+            self.put_on_floor(id, amount - carried + overflow);
+        }
     }
-    /// Returns a boolean indicating whether the player would collide with a machine or border if they moved in the given direction
+
+    /// Returns a boolean indicating whether the player would collide with a machine, the level geometry or a sealed door if they moved in the given direction
     ///
     /// # Arguments
     /// * `next_player_pos` - A tuple containing the next position of the player
@@ -461,7 +717,18 @@ impl GameState {
             .iter()
             .map(|area| area.hitbox)
             .any(|area| is_colliding(next_player_pos, &area))
-            || Self::border_collision_detection(next_player_pos)
+            || self.level.is_blocked(next_player_pos)
+            || self.is_sealed(next_player_pos)
+    }
+    /// Wears down the running machines by one second, worn out machines can break down
+    fn wear_machines(&mut self) -> RLResult {
+        for machine in &mut self.machines {
+            let risk = machine.wear_down();
+            if risk > 0 && self.rng.usize(0..FAILURE_ROLL) < risk {
+                machine.break_down()?;
+            }
+        }
+        Ok(())
     }
     /// Returns the asset if it exists
     /// # Arguments
@@ -474,70 +741,226 @@ impl GameState {
             "Could not find asset with name {name}"
         )))
     }
-    /// Checks if the milestone is reached which means the vec of repaired machines
-    /// contain the vec of machines needed to reach the next milestone.
-    /// # Arguments
-    /// * `milestone_machines` - A vec of machines needed to reach the next milestone
-    /// Author: ["Benedikt Brandmaier","Marion Hinkel"]
-    pub fn check_on_milestone_machines(&mut self, milestone_machines: &[String]) -> bool {
-        let running_machine = self
-            .machines
-            .iter()
-            .filter(|m| m.state != Broken)
-            .map(|m| &m.name)
-            .collect::<Vec<&String>>();
-
-        if milestone_machines
-            .iter()
-            .all(|machine| running_machine.contains(&machine))
-        {
-            return true;
-        }
-        false
-    }
     /// Author: ["Benedikt Brandmaier"]
     fn increase_milestone(&mut self) -> RLResult {
         self.player.milestone += 1;
         info!("Player reached milestone {}", self.player.milestone);
-        self.save(true)?;
+        self.save_in_background(SAVES_PATH, true)?;
         Ok(())
     }
-    /// Decides what happens if a certain milestone is reached
-    /// divided into 3 milestones
+    /// Starts the campaign if necessary and completes every active objective
+    /// whose conditions are fulfilled.
     /// Author: ["Marion Hinkel", "Philipp Wolf"]
-    fn get_current_milestone(&mut self) -> RLResult {
-        match self.player.milestone {
-            0 => {
-                self.player.resources_change.oxygen = -1;
-                self.player.resources_change.energy = -1;
-                self.player.last_damage = 0;
-                self.increase_milestone()?;
+    fn check_objectives(&mut self) -> RLResult {
+        if !self.objectives.is_started() {
+            if let Some(start) = self.objective_catalog.get(&self.objective_catalog.start) {
+                info!("Starting objective {}", start.id);
+                self.objectives
+                    .active
+                    .push((start.id.clone(), self.player.time));
             }
-            1 => {
-                if self.check_on_milestone_machines(&[
-                    MACHINE_NAMES[0].to_string(),
-                    MACHINE_NAMES[1].to_string(),
-                ]) {
-                    self.increase_milestone()?;
-                }
+        }
+        let completed = self
+            .objectives
+            .active
+            .iter()
+            .filter_map(|(id, activated_at)| self.objective_catalog.get(id).zip(Some(activated_at)))
+            .filter(|(objective, activated_at)| {
+                objective.conditions.are_fulfilled(
+                    **activated_at,
+                    &self.player,
+                    &self.machines,
+                    &self.objectives,
+                )
+            })
+            .map(|(objective, _)| objective.clone())
+            .collect::<Vec<Objective>>();
+        for objective in &completed {
+            self.complete_objective(objective)?;
+        }
+        Ok(())
+    }
+    /// Completes an objective: grants its rewards, increases the milestone
+    /// and activates the following objectives.
+    /// # Arguments
+    /// * `objective` - The completed objective
+    fn complete_objective(&mut self, objective: &Objective) -> RLResult {
+        info!("Player completed objective {}", objective.id);
+        self.objectives.active.retain(|(id, _)| *id != objective.id);
+        self.objectives.completed.push(objective.id.clone());
+        let rewards = &objective.rewards;
+        if let Some(resources_change) = rewards.set_resources_change {
+            self.player.resources_change = resources_change;
+        }
+        let items: Vec<(String, i32)> = rewards.items.clone().into_iter().collect();
+        self.add_items(&items);
+        if let Some(popup) = &rewards.popup {
+            let popup = Popup::info(tr!(popup));
+            self.get_screen_sender()?.send(StackCommand::Popup(popup))?;
+        }
+        for next in &objective.next {
+            if !self.objectives.completed.contains(next)
+                && !self.objectives.active.iter().any(|(id, _)| id == next)
+            {
+                info!("Starting objective {}", next);
+                self.objectives
+                    .active
+                    .push((next.clone(), self.player.time));
             }
-            3 => {
-                info!("Player won the Game");
-                self.player.milestone += 1;
-                let cloned_sender = self.get_screen_sender()?.clone();
-                self.get_screen_sender()?.send(StackCommand::Push(Box::new(
-                    InfoScreen::new_winningscreen(cloned_sender),
-                )))?;
+        }
+        if rewards.win {
+            info!("Player won the Game");
+            self.player.milestone += 1;
+            let cloned_sender = self.get_screen_sender()?.clone();
+            let options = self.options.clone();
+            self.get_screen_sender()?.send(StackCommand::Push(Box::new(
+                InfoScreen::new_winningscreen(cloned_sender, options),
+            )))?;
+        } else {
+            self.increase_milestone()?;
+        }
+        Ok(())
+    }
+    /// Returns the outcome of the game or `None` if it is still running
+    pub(crate) fn outcome(&self) -> Option<Outcome> {
+        let won = self.objectives.completed.iter().any(|id| {
+            self.objective_catalog
+                .get(id)
+                .map_or(false, |objective| objective.rewards.win)
+        });
+        if won {
+            Some(Outcome::Won)
+        } else if self.player.resources.life == 0 {
+            Some(Outcome::Died)
+        } else {
+            None
+        }
+    }
+    /// Sets the command line options of the game, lets the `ScriptedAgent` play if `--bot` was given
+    /// and starts recording the input of the player if `--record` was given. The recording is
+    /// written when the game ends or the player returns to the main menu. Has to be called before the game is pushed on the `Screenstack`,
+    /// because the machines must not have sent any `GameCommand` when the recording starts.
+    /// # Arguments
+    /// * `options` - The command line options
+    pub(crate) fn set_options(&mut self, options: Options) -> RLResult {
+        if options.record {
+            self.recorder = Some(Recorder::new(self)?);
+        }
+        if options.bot {
+            self.controller = Some(Box::new(ScriptedAgent::default()));
+        }
+        self.autosave_interval = Settings::load().autosave_interval;
+        self.options = options;
+        Ok(())
+    }
+    /// Returns the input of this tick, which is read from the recording during a replay,
+    /// decided by the bot if one plays and taken from the keyboard otherwise.
+    /// Recorded games also record the input.
+    /// # Arguments
+    /// * `keyboard` - The input read from the keyboard
+    fn next_input(&mut self, keyboard: Input) -> Input {
+        let input = if let Some(replayer) = &mut self.replayer {
+            replayer.next().unwrap_or_default()
+        } else if let Some(mut controller) = self.controller.take() {
+            let input = controller.act(&Observation::new(self));
+            self.controller = Some(controller);
+            input
+        } else {
+            keyboard
+        };
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(input);
+        }
+        input
+    }
+    /// Writes the recording if the recorded game has ended and checks the replayed game
+    /// against its recording once all recorded input was replayed.
+    fn check_recording(&mut self) -> RLResult {
+        let ended = self.outcome().is_some();
+        if ended {
+            if let Some(recorder) = self.recorder.take() {
+                recorder.finish(self)?.save()?;
             }
+        }
+        let replayed = self
+            .replayer
+            .as_ref()
+            .map_or(false, |replayer| ended || replayer.is_finished());
+        if replayed {
+            let expected = self.replayer.take().map_or(0, |replayer| replayer.checksum);
+            let popup = if state_checksum(self)? == expected {
+                Popup::new(RLColor::GREEN, tr!("replay.matches"), 10)
+            } else {
+                Popup::warning(tr!("replay.mismatch"))
+            };
+            self.get_screen_sender()?.send(StackCommand::Popup(popup))?;
+        }
+        Ok(())
+    }
+    /// Pauses the game and opens the `PauseMenu` over it, if it is not open yet
+    pub(crate) fn open_pause_menu(&mut self) -> RLResult {
+        if self.menu_receiver.is_some() {
+            return Ok(());
+        }
+        info!("Opening the pause menu");
+        let (sender, receiver) = channel();
+        self.menu_receiver = Some(receiver);
+        let screen_sender = self.get_screen_sender()?.clone();
+        let menu = PauseMenu::new(screen_sender.clone(), sender);
+        screen_sender.send(StackCommand::Push(Box::new(menu)))?;
+        Ok(())
+    }
+    /// Handles the action chosen in the `PauseMenu` once it was closed
+    fn handle_pause_menu(&mut self) -> RLResult {
+        let Some(receiver) = &self.menu_receiver else {
+            return Ok(());
+        };
+        let action = match receiver.try_recv() {
+            Ok(action) => Some(action),
+            // The menu was closed with `Resume`
+            Err(TryRecvError::Disconnected) => None,
+            // The menu is still open
+            Err(TryRecvError::Empty) => return Ok(()),
+        };
+        self.menu_receiver = None;
+        // The autosave interval may have been changed in the `SettingsMenu`
+        self.autosave_interval = Settings::load().autosave_interval;
+        match action {
+            Some(Message::Save) => self.open_save_slots()?,
+            Some(Message::Handbook) => self.handbook_invisible = false,
+            Some(Message::QuitToMenu) => self.quit_to_menu()?,
             _ => {}
         }
         Ok(())
     }
-    /// Deletes all files in the directory saves, returns Ok if saves directory does not exist
+    /// Opens the `SlotScreen` over the paused game to save it into a slot.
+    /// Games which are never saved, like replays, can not be saved into a slot either.
+    fn open_save_slots(&mut self) -> RLResult {
+        if self.disable_saves {
+            return Ok(());
+        }
+        let game = SlotFile::new(self, String::new())?;
+        let screen_sender = self.get_screen_sender()?.clone();
+        let slots = SlotScreen::new_save(screen_sender.clone(), game);
+        screen_sender.send(StackCommand::Push(Box::new(slots)))?;
+        Ok(())
+    }
+    /// Saves the game, writes the recording if the game is recorded and returns to the main menu
+    fn quit_to_menu(&mut self) -> RLResult {
+        info!("Exiting...");
+        if let Some(recorder) = self.recorder.take() {
+            recorder.finish(self)?.save()?;
+        }
+        self.save(false)?;
+        self.get_screen_sender()?.send(StackCommand::Pop)?;
+        Ok(())
+    }
+    /// Deletes the autosave, its backups and the milestone of the current game, returns Ok if saves directory does not exist.
+    /// The games saved in the save slots are kept.
     /// Author: ["Leo Schnüll"]
     pub(crate) fn delete_saves() -> RLResult {
         info!("deleting saves");
-        let existing_files = fs::read_dir("./saves");
+        let existing_files = fs::read_dir(SAVES_PATH);
         if existing_files.is_err() {
             return Ok(());
         }
@@ -552,13 +975,52 @@ impl GameState {
 }
 
 impl Screen for GameState {
-    /// Updates the game and handles input. Returns `StackCommand::Pop` when Escape is pressed.
+    /// Updates the game and handles input. Escape opens the `PauseMenu`.
+    /// The `GameClock` decides how many ticks run in this frame, P pauses the game and F switches
+    /// between normal speed and fast-forward.
     /// Author: ["Benedikt Brandmaier"]
     fn update(&mut self, ctx: &mut Context) -> RLResult {
-        if ctx.time.check_update_time(DESIRED_FPS) {
-            self.tick()?;
-            self.move_player(ctx)?;
-            Event::update_events(ctx, self)?;
+        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::P) {
+            self.clock.toggle_pause();
+        }
+        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::F) {
+            self.clock.speed = self.clock.speed.next();
+        }
+        self.handle_pause_menu()?;
+        let mut keyboard = Input::from_keyboard(ctx);
+        let scale = get_scale(ctx);
+        let mouse = ctx.mouse.position();
+        let mouse = (mouse.x / scale.x, mouse.y / scale.y);
+        // A click on the interaction menu chooses an option like the number keys
+        if keyboard.choose.is_none() && ctx.mouse.button_just_pressed(MouseButton::Left) {
+            keyboard.choose = self.menu_option_at(mouse);
+        }
+        // A right click on an item of the inventory drops one of it
+        if ctx.mouse.button_just_pressed(MouseButton::Right) {
+            keyboard.drop_item = self.inventory_slot_at(mouse);
+        }
+        // Escape opens the pause menu even if the game time is paused
+        if keyboard.exit {
+            return self.open_pause_menu();
+        }
+        for _ in 0..self.clock.advance(ctx.time.delta()) {
+            let input = self.next_input(keyboard);
+            // Keys pressed in this frame are only handled in its first tick
+            keyboard = keyboard.held();
+            self.step(input)?;
+            self.check_recording()?;
+            if self.menu_receiver.is_some() || self.outcome().is_some() {
+                break;
+            }
+        }
+        // If we are in debug mode, change the milestone by using Z
+        // This is not possible in recorded games, because it is no input of the game
+        #[cfg(debug_assertions)]
+        if self.recorder.is_none()
+            && self.replayer.is_none()
+            && ctx.keyboard.is_key_just_pressed(VirtualKeyCode::Z)
+        {
+            self.player.milestone += 1;
         }
         Ok(())
     }
@@ -571,8 +1033,9 @@ impl Screen for GameState {
     fn draw(&self, ctx: &mut Context) -> RLResult {
         let scale = get_scale(ctx);
         let mut canvas = Canvas::from_frame(ctx, graphics::Color::from([0.1, 0.2, 0.3, 1.0]));
-        let background = self.get_asset("basis.png")?;
+        let background = self.get_asset(&self.level.background)?;
         canvas.draw(background, graphics::DrawParam::default().scale(scale));
+        self.draw_floor_items(&mut canvas, scale)?;
         let player = self.get_asset("player.png")?;
         draw!(
             canvas,
@@ -580,17 +1043,26 @@ impl Screen for GameState {
             Vec2::from([self.player.position.0 as f32, self.player.position.1 as f32]),
             scale
         );
+        self.draw_atmosphere(&mut canvas, scale, ctx)?;
         self.draw_resources(&mut canvas, scale, ctx)?;
         self.draw_machines(&mut canvas, scale, ctx)?;
         self.draw_items(&mut canvas, ctx)?;
         if !self.handbook_invisible {
             self.open_handbook(&mut canvas, ctx)?;
         }
+        if self.grid_visible {
+            self.draw_grid(&mut canvas, scale, ctx)?;
+        }
+        self.draw_interaction_menu(&mut canvas, scale, ctx)?;
         #[cfg(debug_assertions)]
         {
             let fps = graphics::Text::new(format!("FPS: {}", ctx.time.fps()));
             draw!(canvas, &fps, Vec2::new(1400.0, 0.0), scale);
-            let milestone = graphics::Text::new(format!("Milestone: {}", self.player.milestone));
+            let milestone = graphics::Text::new(format!(
+                "Milestone: {} Seed: {}",
+                self.player.milestone,
+                self.rng.seed()
+            ));
             draw!(canvas, &milestone, Vec2::new(1400.0, 20.0), scale);
             let events = graphics::Text::new(format!("Events: {:?}", self.events));
             draw!(canvas, &events, Vec2::new(1400.0, 40.0), scale);
@@ -623,29 +1095,308 @@ impl Screen for GameState {
         self.screen_sender = Some(sender);
         self.init_all_machines();
     }
+    /// Returns the time of the game in ticks
+    fn game_time(&self) -> Option<u32> {
+        Some(self.player.time)
+    }
+    /// Opens the pause menu when the window loses the focus
+    fn focus_lost(&mut self) -> RLResult {
+        self.open_pause_menu()
+    }
+}
+#[cfg(test)]
+impl GameState {
+    /// Creates a new game for tests which does not write any saves
+    /// # Arguments
+    /// * `seed` - The seed of the random number generator
+    /// # Returns
+    /// * `(GameState, Receiver<StackCommand>)` - The game and the receiver of its screen commands
+    pub(crate) fn test_game(seed: u64) -> (GameState, Receiver<StackCommand>) {
+        let mut gamestate = GameState::new_game(GameData::bundled(), seed).unwrap();
+        gamestate.disable_saves = true;
+        let (sender, receiver) = channel();
+        gamestate.set_sender(sender);
+        (gamestate, receiver)
+    }
+
+    /// Returns the index of the first machine with a name
+    /// # Arguments
+    /// * `name` - The name of the machine
+    pub(crate) fn machine_index(&self, name: &str) -> usize {
+        self.machines
+            .iter()
+            .position(|machine| machine.name == name)
+            .unwrap()
+    }
+
+    /// Moves the player into the interaction area of a machine
+    /// # Arguments
+    /// * `index` - The index of the machine
+    #[allow(clippy::cast_sign_loss)]
+    pub(crate) fn stand_next_to(&mut self, index: usize) {
+        let area = self.machines[index].interaction_area;
+        self.player.position = (area.x as usize + 1, area.y as usize + 1);
+    }
 }
+
 #[cfg(test)]
 /// Author: ["Benedikt Brandmaier", "Maximilian Floto", "Leo Schnüll", "Philipp Wolf"]
 mod test {
     use super::*;
+    use crate::machines::machine::{MachineOption, State};
+    use crate::machines::trade::Trade;
 
     #[test]
     fn test_gamestate() {
         let _gamestate = GameState::default();
     }
 
+    #[test]
+    fn test_pause_menu_opens_once() {
+        let (mut gamestate, receiver) = GameState::test_game(1);
+        gamestate.open_pause_menu().unwrap();
+        gamestate.open_pause_menu().unwrap();
+        let menus: Vec<_> = receiver
+            .try_iter()
+            .filter(|command| matches!(command, StackCommand::Push(_)))
+            .collect();
+        assert_eq!(menus.len(), 1);
+        gamestate.handle_pause_menu().unwrap();
+        assert!(gamestate.menu_receiver.is_some());
+        // Closing the menu without an action resumes the game
+        drop(menus);
+        gamestate.handle_pause_menu().unwrap();
+        assert!(gamestate.menu_receiver.is_none());
+    }
+
     #[test]
     fn test_save_autosave() {
-        let gamestate = GameState::default();
+        let mut gamestate = GameState::default();
         gamestate.save(false).unwrap();
     }
 
     #[test]
     fn test_save_milestone() {
-        let gamestate = GameState::default();
+        let mut gamestate = GameState::default();
         gamestate.save(true).unwrap();
     }
 
+    #[test]
+    fn test_save_in_background() {
+        // Every test binary uses its own folder, so the saves of the player are not touched
+        let dir = std::env::temp_dir().join(format!("red-life-saves-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let dir = dir.to_string_lossy().to_string();
+        let mut gamestate = GameState::default();
+        for time in [1, 2, 3] {
+            gamestate.player.time = time;
+            gamestate.save_in_background(&dir, time == 3).unwrap();
+        }
+        gamestate.finish_background_save().unwrap();
+        assert!(gamestate.background_save.is_none());
+        let read = |path: String| save_file::read_with_backups(&path, 0).unwrap().0;
+        assert_eq!(read(format!("{dir}/{AUTOSAVE_FILE}")).player.time, 3);
+        assert_eq!(read(format!("{dir}/{MILESTONE_FILE}")).player.time, 3);
+        // The older autosaves are kept as backups
+        let autosave = format!("{dir}/{AUTOSAVE_FILE}");
+        assert_eq!(read(save_file::backup_path(&autosave, 1)).player.time, 2);
+        assert_eq!(read(save_file::backup_path(&autosave, 2)).player.time, 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_periodic_autosave() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        gamestate.autosave_interval = 1;
+        while gamestate.player.time < 60 * DESIRED_FPS - 1 {
+            gamestate.tick().unwrap();
+            assert!(!gamestate.is_autosave_due());
+        }
+        gamestate.tick().unwrap();
+        assert!(gamestate.is_autosave_due());
+        // No autosave is scheduled without an interval
+        gamestate.autosave_interval = 0;
+        assert!(!gamestate.is_autosave_due());
+    }
+
+    #[test]
+    fn test_worn_machine_breaks_down() {
+        let (mut gamestate, _receiver) = GameState::test_game(3);
+        let index = gamestate.machine_index("Sauerstoffgenerator");
+        gamestate.machines[index].change_state_to(&State::Running);
+        let mut seconds = 0;
+        while gamestate.machines[index].state == State::Running {
+            gamestate.wear_machines().unwrap();
+            seconds += 1;
+        }
+        assert_eq!(gamestate.machines[index].state, State::Broken);
+        // The machine only breaks down once it is worn out
+        assert!(seconds > 150);
+        // Repairing the machine makes it as good as new
+        let glue = gamestate.item_registry.get("super_glue").unwrap().clone();
+        gamestate.player.add_item(&glue, 2);
+        let machine = &mut gamestate.machines[index];
+        machine
+            .interact(&gamestate.player, &gamestate.item_registry)
+            .unwrap();
+        while machine.is_busy() {
+            machine.tick().unwrap();
+        }
+        assert_eq!(machine.state, State::Idle);
+        assert!((machine.get_durability_percentage() - 1.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_maintenance_restores_durability() {
+        let (mut gamestate, _receiver) = GameState::test_game(3);
+        let index = gamestate.machine_index("Sauerstoffgenerator");
+        gamestate.machines[index].change_state_to(&State::Running);
+        while gamestate.machines[index].get_durability_percentage() > 0.5 {
+            gamestate.wear_machines().unwrap();
+        }
+        // The machine is maintained before it breaks down
+        gamestate.machines[index].change_state_to(&State::Idle);
+        let glue = gamestate.item_registry.get("super_glue").unwrap().clone();
+        gamestate.player.add_item(&glue, 1);
+        let machine = &mut gamestate.machines[index];
+        let maintenance = machine
+            .options()
+            .into_iter()
+            .find(|option| {
+                matches!(option, MachineOption::Trade(trade) if machine.trades[*trade].restores_durability)
+            })
+            .unwrap();
+        machine
+            .choose(maintenance, &gamestate.player, &gamestate.item_registry)
+            .unwrap();
+        while machine.is_busy() {
+            machine.tick().unwrap();
+        }
+        assert_eq!(machine.state, State::Idle);
+        assert!((machine.get_durability_percentage() - 1.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_resume_halted_production() {
+        let (mut gamestate, _receiver) = GameState::test_game(3);
+        let index = gamestate.machine_index("Werkermaschine");
+        gamestate.machines[index].change_state_to(&State::Idle);
+        for _ in 0..2 {
+            gamestate.machines[index]
+                .interact(&gamestate.player, &gamestate.item_registry)
+                .unwrap();
+        }
+        gamestate.tick().unwrap();
+        assert_eq!(gamestate.machines[index].state, State::Running);
+        gamestate.machines[index].no_energy();
+        assert!(gamestate.machines[index].is_halted());
+        assert!(!gamestate.machines[index].is_busy());
+        // Resuming the production is a step of its own, which queues no further unit
+        let queued = gamestate.machines[index].queued_units();
+        gamestate.machines[index]
+            .interact(&gamestate.player, &gamestate.item_registry)
+            .unwrap();
+        assert!(!gamestate.machines[index].is_halted());
+        assert_eq!(gamestate.machines[index].queued_units(), queued);
+        gamestate.tick().unwrap();
+        assert_eq!(gamestate.machines[index].state, State::Running);
+    }
+
+    #[test]
+    fn test_recipe_queue() {
+        let (mut gamestate, _receiver) = GameState::test_game(3);
+        let index = gamestate.machine_index("Werkermaschine");
+        gamestate.machines[index].change_state_to(&State::Idle);
+        for _ in 0..3 {
+            gamestate.machines[index]
+                .interact(&gamestate.player, &gamestate.item_registry)
+                .unwrap();
+        }
+        assert_eq!(gamestate.machines[index].queued_units(), 3);
+        let glue = gamestate.player.get_item_amount("super_glue");
+        gamestate.tick().unwrap();
+        assert_eq!(gamestate.machines[index].state, State::Running);
+        // The units are produced one after another without any further interaction
+        while gamestate.machines[index].is_busy() {
+            gamestate.tick().unwrap();
+        }
+        for _ in 0..10 {
+            gamestate.tick().unwrap();
+        }
+        assert_eq!(gamestate.machines[index].state, State::Idle);
+        assert_eq!(gamestate.machines[index].queued_units(), 0);
+        // The units wait in the output buffer until the player walks over to collect them
+        assert_eq!(gamestate.player.get_item_amount("super_glue"), glue);
+        assert_eq!(gamestate.machines[index].buffered_units(), 3);
+        gamestate.stand_next_to(index);
+        gamestate.move_player(Input::default()).unwrap();
+        assert_eq!(gamestate.player.get_item_amount("super_glue"), glue + 3);
+        assert_eq!(gamestate.machines[index].buffered_units(), 0);
+    }
+
+    #[test]
+    fn test_full_output_pauses_production() {
+        let (mut gamestate, _receiver) = GameState::test_game(3);
+        let index = gamestate.machine_index("Werkermaschine");
+        let machine = &mut gamestate.machines[index];
+        machine.output_capacity = 1;
+        machine.change_state_to(&State::Idle);
+        for _ in 0..2 {
+            machine
+                .interact(&gamestate.player, &gamestate.item_registry)
+                .unwrap();
+        }
+        for _ in 0..500 {
+            gamestate.tick().unwrap();
+        }
+        // The second unit does not fit into the buffer, so the machine waits without running
+        let machine = &gamestate.machines[index];
+        assert_eq!(machine.state, State::Idle);
+        assert_eq!(machine.queued_units(), 1);
+        assert!(machine.is_output_full());
+        gamestate.collect_outputs();
+        assert_eq!(gamestate.machines[index].buffered_units(), 1);
+        gamestate.stand_next_to(index);
+        gamestate.collect_outputs();
+        gamestate.tick().unwrap();
+        assert_eq!(gamestate.machines[index].state, State::Running);
+    }
+
+    #[test]
+    fn test_full_output_blocks_trade() {
+        let (mut gamestate, receiver) = GameState::test_game(1);
+        let index = gamestate.machine_index("Werkermaschine");
+        let machine = &mut gamestate.machines[index];
+        machine.trades = vec![Trade::new(
+            "sell_glue".to_string(),
+            10,
+            State::Broken,
+            State::Running,
+            true,
+            vec![("super_glue".to_string(), -3)],
+            false,
+        )];
+        machine.output = vec![("printed_part".to_string(), 3)];
+        machine
+            .interact(&gamestate.player, &gamestate.item_registry)
+            .unwrap();
+        // The outputs of the trade would not fit into the buffer, so the trade does not start
+        assert!(!gamestate.machines[index].is_busy());
+        assert!(receiver
+            .try_iter()
+            .any(|command| matches!(command, StackCommand::Popup(_))));
+        gamestate.stand_next_to(index);
+        gamestate.collect_outputs();
+        let machine = &mut gamestate.machines[index];
+        machine
+            .interact(&gamestate.player, &gamestate.item_registry)
+            .unwrap();
+        while machine.is_busy() {
+            machine.tick().unwrap();
+        }
+        assert_eq!(machine.output, vec![("super_glue".to_string(), 3)]);
+    }
+
     #[test]
     fn test_load_autosave() {
         GameState::default().save(false).unwrap();
@@ -1,23 +1,44 @@
 //! This File handels code surrounding Machine with in `GameState`
 //! Author: ["Benedikt Brandmaier", "Sander Stella", "Philipp Wolf"]
-use crate::backend::constants::gen_all_machines;
+use crate::backend::constants::{DURABILITY_THRESHOLD, MAX_DURABILITY};
 use crate::backend::gamestate::GameState;
 use crate::backend::rlcolor::RLColor;
 use crate::backend::utils::get_draw_params;
 use crate::{draw, RLResult};
 use ggez::glam::Vec2;
-use ggez::graphics::{Canvas, Mesh, Rect};
+use ggez::graphics::{Canvas, Mesh, Rect, Text, TextFragment};
 use ggez::Context;
 use tracing::info;
 
+/// The durability below which a machine can break down, as a fraction of its full durability
+const DURABILITY_THRESHOLD_PERCENTAGE: f32 = DURABILITY_THRESHOLD as f32 / MAX_DURABILITY as f32;
+
 impl GameState {
-    /// Creates all Machines for initial creation and pushes them into a list
-    pub fn create_machine(&mut self) {
-        info!("Generating all Machines");
-        self.machines = gen_all_machines();
+    /// Collects the output buffers of the machines the player stands next to.
+    /// The items the player can not carry stay in the buffers.
+    pub(crate) fn collect_outputs(&mut self) {
+        let position = self.player.position;
+        for machine in &mut self.machines {
+            if !machine.is_interactable(position) {
+                continue;
+            }
+            for (id, amount) in &mut machine.output {
+                let Some(item) = self.item_registry.get(id) else {
+                    continue;
+                };
+                let collected = (*amount).min(self.player.room_for(item, &self.item_registry));
+                if collected > 0 {
+                    info!("Collected {} {} from {}", collected, id, machine.name);
+                    self.player.add_item(item, collected);
+                    *amount -= collected;
+                }
+            }
+            machine.output.retain(|(_, amount)| *amount > 0);
+        }
     }
 
-    /// Paints the machine sprites and if applicable it shows the state or time remaining
+    /// Paints the machine sprites and if applicable it shows the state, the time remaining, the queue
+    /// or the output waiting to be collected
     /// # Arguments
     /// * `canvas`: The canvas to draw on
     /// * `scale`: The scale of the canvas
@@ -45,6 +66,21 @@ impl GameState {
                 pos.x += 20.;
                 pos.y += 20.;
                 draw!(canvas, &status, pos, scale);
+                // Draws the durability next to the machine status
+                if machine.wears_down() {
+                    let durability = machine.get_durability_percentage();
+                    let color = if durability < DURABILITY_THRESHOLD_PERCENTAGE {
+                        RLColor::STATUS_RED
+                    } else {
+                        RLColor::BLACK
+                    };
+                    let text = Text::new(
+                        TextFragment::new(format!("{:.0}%", durability * 100.))
+                            .color(color)
+                            .scale(24.),
+                    );
+                    draw!(canvas, &text, Vec2::new(pos.x + 20., pos.y - 12.), scale);
+                }
             };
             // Draws the machine timer on top of the machine
             let time = machine.get_time_percentage();
@@ -70,6 +106,49 @@ impl GameState {
                 )?;
                 draw!(canvas, &rect2, pos, scale);
             }
+            // Draws the amount of queued units next to the timer
+            let queued = machine.queued_units();
+            if queued > 0 {
+                let text = Text::new(
+                    TextFragment::new(format!("x{queued}"))
+                        .color(RLColor::BLACK)
+                        .scale(24.),
+                );
+                draw!(
+                    canvas,
+                    &text,
+                    Vec2::new(machine.hitbox.x + 200., machine.hitbox.y - 12.),
+                    scale
+                );
+            }
+            // Draws the first item of the output buffer and the amount of items above the machine
+            let Some(item) = machine
+                .output
+                .first()
+                .and_then(|(id, _)| self.item_registry.get(id))
+            else {
+                continue;
+            };
+            let icon = Vec2::new(
+                machine.hitbox.x + machine.hitbox.w - 60.,
+                machine.hitbox.y - 70.,
+            );
+            draw!(canvas, self.get_asset(&item.img)?, icon, scale);
+            let color = if machine.is_output_full() {
+                RLColor::STATUS_RED
+            } else {
+                RLColor::BLACK
+            };
+            let text = Text::new(
+                TextFragment::new(format!(
+                    "{}/{}",
+                    machine.buffered_units(),
+                    machine.output_capacity
+                ))
+                .color(color)
+                .scale(24.),
+            );
+            draw!(canvas, &text, Vec2::new(icon.x + 60., icon.y + 10.), scale);
         }
         Ok(())
     }
@@ -0,0 +1,318 @@
+//! Contains the level format, which describes the world the game is played in.
+use crate::backend::error::create_data_error;
+use crate::backend::utils::is_colliding;
+use crate::machines::machine::{Machine, State};
+use crate::RLResult;
+use ggez::graphics::Rect;
+use serde::Deserialize;
+use std::fs;
+use tracing::info;
+
+/// Describes the background, the walkable area and the machines of a habitat
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    /// The file the level was loaded from, used for error messages
+    #[serde(skip)]
+    file: String,
+    /// The file name of the background image in the assets folder
+    pub(crate) background: String,
+    /// The position of the player when a new game starts
+    pub(crate) spawn: (usize, usize),
+    /// Polygons given by their corners, the player can only stand inside of them
+    walkable: Vec<Vec<(f32, f32)>>,
+    /// Areas the player can not walk into
+    #[serde(default)]
+    obstacles: Vec<Rect>,
+    /// The machines of the level and where they stand
+    machines: Vec<MachinePlacement>,
+    /// The rooms of the habitat, each with its own atmosphere.
+    /// A level without rooms is one room covering everything.
+    #[serde(default)]
+    pub(crate) rooms: Vec<Room>,
+    /// The doors between the rooms, open doors let the air flow between them
+    #[serde(default)]
+    pub(crate) doors: Vec<Door>,
+}
+
+/// A room of the habitat, machines and the player are in the room their position lies in
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Room {
+    /// The name of the room, used by the doors and to look up its message id `room.<name>`
+    pub(crate) name: String,
+    /// The area of the room including its walls
+    pub(crate) area: Rect,
+}
+
+/// A door connecting two rooms, the player can not walk through a sealed door
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Door {
+    /// The names of the two rooms the door connects
+    rooms: (String, String),
+    /// The area the door blocks while it is sealed
+    pub(crate) area: Rect,
+    /// Whether the door is open when a new game starts
+    #[serde(default = "door_open")]
+    pub(crate) open: bool,
+    /// The indices of the two rooms the door connects
+    #[serde(skip)]
+    pub(crate) connects: (usize, usize),
+}
+
+/// Returns that doors are open by default
+fn door_open() -> bool {
+    true
+}
+
+/// Places a machine of the machine catalog in a level
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MachinePlacement {
+    /// The name of the machine in the machine catalog
+    name: String,
+    /// The position of the top left corner of the machine
+    position: (f32, f32),
+    /// The state the machine starts in, machines start broken by default
+    #[serde(default)]
+    state: Option<State>,
+}
+
+impl Level {
+    /// Loads the level at the given path.
+    /// # Arguments
+    /// * `path` - The path of the level file
+    /// # Returns
+    /// * `RLResult<Level>` - The validated level or an error describing the invalid entry
+    pub(crate) fn load(path: &str) -> RLResult<Self> {
+        info!("Loading level from {}", path);
+        let source = fs::read_to_string(path).map_err(|e| create_data_error(path, "file", e))?;
+        Self::parse(path, &source)
+    }
+
+    /// Parses and validates the content of a level file.
+    /// # Arguments
+    /// * `file` - The name of the file the content was read from, used for error messages
+    /// * `source` - The content of the level file
+    /// # Returns
+    /// * `RLResult<Level>` - The validated level or an error describing the invalid entry
+    pub(crate) fn parse(file: &str, source: &str) -> RLResult<Self> {
+        let mut level: Level =
+            serde_yaml::from_str(source).map_err(|e| create_data_error(file, "level", e))?;
+        level.file = file.to_string();
+        if level.walkable.is_empty() {
+            return Err(create_data_error(
+                file,
+                "walkable",
+                "a level needs at least one walkable area",
+            ));
+        }
+        if let Some(i) = level.walkable.iter().position(|polygon| polygon.len() < 3) {
+            return Err(create_data_error(
+                file,
+                &format!("walkable[{i}]"),
+                "a polygon needs at least three points",
+            ));
+        }
+        if let Some(i) = level
+            .obstacles
+            .iter()
+            .position(|area| area.w <= 0. || area.h <= 0.)
+        {
+            return Err(create_data_error(
+                file,
+                &format!("obstacles[{i}]"),
+                "the size must be greater than zero",
+            ));
+        }
+        if let Some(i) = level
+            .machines
+            .iter()
+            .position(|machine| machine.position.0 < 0. || machine.position.1 < 0.)
+        {
+            return Err(create_data_error(
+                file,
+                &format!("machines[{i}].position"),
+                "the position must not be negative",
+            ));
+        }
+        if level.rooms.is_empty() {
+            level.rooms.push(Room {
+                name: "habitat".to_string(),
+                area: Rect::new(0., 0., f32::MAX, f32::MAX),
+            });
+        }
+        for (i, room) in level.rooms.iter().enumerate() {
+            if level.rooms[..i].iter().any(|other| other.name == room.name) {
+                return Err(create_data_error(
+                    file,
+                    &format!("rooms[{i}].name"),
+                    format!("the room \"{}\" exists twice", room.name),
+                ));
+            }
+        }
+        for i in 0..level.doors.len() {
+            let (first, second) = &level.doors[i].rooms;
+            let find = |name: &String| {
+                level
+                    .rooms
+                    .iter()
+                    .position(|room| room.name == *name)
+                    .ok_or_else(|| {
+                        create_data_error(
+                            file,
+                            &format!("doors[{i}].rooms"),
+                            format!("unknown room \"{name}\""),
+                        )
+                    })
+            };
+            level.doors[i].connects = (find(first)?, find(second)?);
+        }
+        if level.is_blocked(level.spawn) {
+            return Err(create_data_error(
+                file,
+                "spawn",
+                "the player has to spawn inside of a walkable area and outside of all obstacles",
+            ));
+        }
+        Ok(level)
+    }
+
+    /// Returns whether the player can not stand at the given position because of the level geometry
+    /// # Arguments
+    /// * `position` - The position of the player
+    /// # Returns
+    /// * `true` if the position is outside of all walkable areas or inside of an obstacle
+    pub fn is_blocked(&self, position: (usize, usize)) -> bool {
+        let point = (position.0 as f32, position.1 as f32);
+        !self
+            .walkable
+            .iter()
+            .any(|polygon| contains_point(polygon, point))
+            || self
+                .obstacles
+                .iter()
+                .any(|area| is_colliding(position, area))
+    }
+
+    /// Returns the room a position lies in
+    /// # Arguments
+    /// * `position` - The position to look up
+    /// # Returns
+    /// * `Option<usize>` - The index of the room, `None` if the position is outside of all rooms
+    pub(crate) fn room_at(&self, position: (f32, f32)) -> Option<usize> {
+        self.rooms
+            .iter()
+            .position(|room| room.area.contains([position.0, position.1]))
+    }
+
+    /// Creates the machines of the level by placing the machines of the machine catalog
+    /// # Arguments
+    /// * `catalog` - All machines of the machine catalog
+    /// # Returns
+    /// * `RLResult<Vec<Machine>>` - The placed machines or an error if a machine is unknown
+    pub(crate) fn place_machines(&self, catalog: &[Machine]) -> RLResult<Vec<Machine>> {
+        self.machines
+            .iter()
+            .enumerate()
+            .map(|(i, placement)| {
+                let mut machine = catalog
+                    .iter()
+                    .find(|machine| machine.name == placement.name)
+                    .ok_or_else(|| {
+                        create_data_error(
+                            &self.file,
+                            &format!("machines[{i}].name"),
+                            format!("unknown machine \"{}\"", placement.name),
+                        )
+                    })?
+                    .clone();
+                machine.place(placement.position, placement.state.clone());
+                Ok(machine)
+            })
+            .collect()
+    }
+}
+
+/// Returns whether a point lies inside of a polygon by casting a ray to the right
+/// and counting how many edges of the polygon it crosses
+/// # Arguments
+/// * `polygon` - The corners of the polygon
+/// * `point` - The point to check
+fn contains_point(polygon: &[(f32, f32)], point: (f32, f32)) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &current in polygon {
+        if (current.1 > point.1) != (previous.1 > point.1)
+            && point.0
+                < (previous.0 - current.0) * (point.1 - current.1) / (previous.1 - current.1)
+                    + current.0
+        {
+            inside = !inside;
+        }
+        previous = current;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::error::RLError;
+    use crate::game_core::item::ItemRegistry;
+    use crate::machines::catalog::parse_machine_catalog;
+
+    const LEVEL: &str = include_str!("../../../assets/levels/habitat.yaml");
+
+    #[test]
+    fn test_parse_level() {
+        let level = Level::parse("habitat.yaml", LEVEL).unwrap();
+        let items = ItemRegistry::parse(
+            "items.yaml",
+            include_str!("../../../assets/data/items.yaml"),
+        )
+        .unwrap();
+        let catalog = parse_machine_catalog(
+            "machines.yaml",
+            include_str!("../../../assets/data/machines.yaml"),
+            &items,
+        )
+        .unwrap();
+        let machines = level.place_machines(&catalog).unwrap();
+        assert_eq!(machines.len(), 7);
+        assert_eq!(machines[6].hitbox, Rect::new(680., 900., 32., 18.));
+    }
+
+    #[test]
+    fn test_walkable_polygon() {
+        let source = "background: basis.png
+spawn: [10, 10]
+walkable:
+  - [[0, 0], [100, 0], [0, 100]]
+obstacles:
+  - { x: 20.0, y: 0.0, w: 10.0, h: 10.0 }
+machines: []
+";
+        let level = Level::parse("test.yaml", source).unwrap();
+        assert!(!level.is_blocked((10, 10)));
+        assert!(level.is_blocked((60, 60)));
+        assert!(level.is_blocked((101, 10)));
+        // The player icon overlaps with the obstacle
+        assert!(level.is_blocked((5, 5)));
+    }
+
+    #[test]
+    fn test_spawn_outside_of_walkable_area() {
+        let source = "background: basis.png
+spawn: [200, 200]
+walkable:
+  - [[0, 0], [100, 0], [100, 100], [0, 100]]
+machines: []
+";
+        assert!(matches!(
+            Level::parse("test.yaml", source),
+            Err(RLError::InvalidData(_))
+        ));
+    }
+}
@@ -1,8 +1,18 @@
-pub(crate) mod constants;
-pub(crate) mod error;
-pub(crate) mod gamestate;
+pub(crate) mod clock;
+pub mod constants;
+pub mod error;
+pub mod game_data;
+pub mod gamestate;
 pub(crate) mod generate_machines;
+pub(crate) mod level;
 pub(crate) mod movement;
+pub mod options;
+pub mod recording;
 pub(crate) mod rlcolor;
-pub(crate) mod screen;
-pub(crate) mod utils;
+pub(crate) mod rng;
+pub mod save_file;
+pub(crate) mod save_slots;
+pub mod screen;
+pub mod settings;
+pub mod simulation;
+pub mod utils;
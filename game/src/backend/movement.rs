@@ -3,83 +3,172 @@
 
 use crate::backend::constants::MOVEMENT_SPEED;
 use crate::backend::gamestate::GameState;
-use crate::backend::screen::StackCommand;
 use crate::RLResult;
 use ggez::winit::event::VirtualKeyCode;
 use ggez::Context;
-use tracing::info;
+use serde::{Deserialize, Serialize};
+
+/// The keys choosing an option of the interaction menu or raising the priority of a consumer in
+/// the power grid panel, by their place
+const CHOICE_KEYS: [VirtualKeyCode; 9] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+];
+
+/// The input of the player in a single tick, it is read from the keyboard or created by a `Simulation`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // Every key is pressed or not
+#[serde(default)]
+pub struct Input {
+    /// Moves the player up (W)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) up: bool,
+    /// Moves the player left (A)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) left: bool,
+    /// Moves the player down (S)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) down: bool,
+    /// Moves the player right (D)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) right: bool,
+    /// Picks up the items next to the player, interacts with the nearest machine or opens and
+    /// seals the nearest door (E)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) interact: bool,
+    /// Opens or closes the handbook (H)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) toggle_handbook: bool,
+    /// Opens or closes the power grid panel (G)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) toggle_grid: bool,
+    /// Chooses the option at this place of the open interaction menu or raises the priority of
+    /// the consumer at this place of the open power grid panel, starting at 0 (1 to 9 or a click
+    /// on the menu)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) choose: Option<usize>,
+    /// Drops one item of this place of the inventory on the floor, starting at 0 (right click on
+    /// the item)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) drop_item: Option<usize>,
+    /// Saves the game and returns to the main menu (Escape)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) exit: bool,
+}
+
+impl Input {
+    /// Reads the input of the player from the keyboard
+    /// # Arguments
+    /// * `ctx` - The game context which is needed to get the pressed keys
+    /// # Returns
+    /// * `Input` - The input of the player in this tick
+    pub(crate) fn from_keyboard(ctx: &Context) -> Self {
+        let keyboard = &ctx.keyboard;
+        Self {
+            up: keyboard.is_key_pressed(VirtualKeyCode::W),
+            left: keyboard.is_key_pressed(VirtualKeyCode::A),
+            down: keyboard.is_key_pressed(VirtualKeyCode::S),
+            right: keyboard.is_key_pressed(VirtualKeyCode::D),
+            interact: keyboard.is_key_just_pressed(VirtualKeyCode::E),
+            toggle_handbook: keyboard.is_key_just_pressed(VirtualKeyCode::H),
+            toggle_grid: keyboard.is_key_just_pressed(VirtualKeyCode::G),
+            choose: CHOICE_KEYS
+                .iter()
+                .position(|key| keyboard.is_key_just_pressed(*key)),
+            drop_item: None,
+            exit: keyboard.is_key_just_pressed(VirtualKeyCode::Escape),
+        }
+    }
+
+    /// Returns the keys which are held down, without the keys which only act when they are pressed
+    pub(crate) fn held(self) -> Self {
+        Self {
+            interact: false,
+            toggle_handbook: false,
+            toggle_grid: false,
+            choose: None,
+            drop_item: None,
+            exit: false,
+            ..self
+        }
+    }
+}
 
 impl GameState {
     /// Handles the player movement and updates the player position
     /// Checks on every move if the next step is inside the borders of the map if not it will not move
-    /// Handles escape which will pause the game and go to the main menu
+    /// Handles escape which will pause the game and open the pause menu
     ///  # Arguments
-    /// * `ctx` - The game context which is needed to get the pressed keys
+    /// * `input` - The input of the player in this tick
     /// # Returns
     /// * `RLResult<()>` - Returns okay, if no Error occurred
-    pub fn move_player(&mut self, ctx: &mut Context) -> RLResult {
-        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::Escape) {
-            info!("Exiting...");
-            self.save(false)?;
-            self.get_screen_sender()?.send(StackCommand::Pop)?;
+    pub(crate) fn move_player(&mut self, input: Input) -> RLResult {
+        if input.exit {
+            self.open_pause_menu()?;
         }
-        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::E) {
-            info!("Interacting with Area: {:?}", self.get_interactable());
-            let player_ref = &self.player.clone();
-            if let Some(interactable) = self.get_interactable() {
-                interactable.interact(player_ref)?;
+        if input.interact {
+            // E closes the interaction menu again
+            if self.interaction_menu.take().is_none() && !self.pick_up_items()? {
+                self.interact()?;
             }
         }
-        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::H) {
+        if let Some(slot) = input.drop_item {
+            self.drop_item(slot);
+        }
+        if input.toggle_handbook {
             self.handbook_invisible = !self.handbook_invisible;
         }
-        // If we are in debug mode, change the milestone by using Z
-        #[cfg(debug_assertions)]
-        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::Z) {
-            self.player.milestone += 1;
+        if input.toggle_grid {
+            self.grid_visible = !self.grid_visible;
         }
-        let keys = ctx.keyboard.pressed_keys();
-        for key in keys.iter() {
-            match key {
-                VirtualKeyCode::W => {
-                    if !self.collision_detection((
-                        self.player.position.0,
-                        self.player.position.1.saturating_sub(MOVEMENT_SPEED),
-                    )) {
-                        self.player.position.1 =
-                            self.player.position.1.saturating_sub(MOVEMENT_SPEED);
-                    }
-                }
-                VirtualKeyCode::A => {
-                    if !self.collision_detection((
-                        self.player.position.0.saturating_sub(MOVEMENT_SPEED),
-                        self.player.position.1,
-                    )) {
-                        self.player.position.0 =
-                            self.player.position.0.saturating_sub(MOVEMENT_SPEED);
-                    }
-                }
-                VirtualKeyCode::S => {
-                    if !self.collision_detection((
-                        self.player.position.0,
-                        self.player.position.1.saturating_add(MOVEMENT_SPEED),
-                    )) {
-                        self.player.position.1 =
-                            self.player.position.1.saturating_add(MOVEMENT_SPEED);
-                    }
-                }
-                VirtualKeyCode::D => {
-                    if !self.collision_detection((
-                        self.player.position.0.saturating_add(MOVEMENT_SPEED),
-                        self.player.position.1,
-                    )) {
-                        self.player.position.0 =
-                            self.player.position.0.saturating_add(MOVEMENT_SPEED);
-                    }
-                }
-                _ => {}
+        if let Some(index) = input.choose {
+            if self.interaction_menu.is_some() {
+                self.choose_option(index)?;
+            } else if self.grid_visible {
+                self.grid.raise(&self.machines, index);
             }
         }
+        if input.up
+            && !self.collision_detection((
+                self.player.position.0,
+                self.player.position.1.saturating_sub(MOVEMENT_SPEED),
+            ))
+        {
+            self.player.position.1 = self.player.position.1.saturating_sub(MOVEMENT_SPEED);
+        }
+        if input.left
+            && !self.collision_detection((
+                self.player.position.0.saturating_sub(MOVEMENT_SPEED),
+                self.player.position.1,
+            ))
+        {
+            self.player.position.0 = self.player.position.0.saturating_sub(MOVEMENT_SPEED);
+        }
+        if input.down
+            && !self.collision_detection((
+                self.player.position.0,
+                self.player.position.1.saturating_add(MOVEMENT_SPEED),
+            ))
+        {
+            self.player.position.1 = self.player.position.1.saturating_add(MOVEMENT_SPEED);
+        }
+        if input.right
+            && !self.collision_detection((
+                self.player.position.0.saturating_add(MOVEMENT_SPEED),
+                self.player.position.1,
+            ))
+        {
+            self.player.position.0 = self.player.position.0.saturating_add(MOVEMENT_SPEED);
+        }
+        self.check_interaction_menu();
+        self.collect_outputs();
 
         Ok(())
     }
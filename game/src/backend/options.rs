@@ -0,0 +1,94 @@
+//! Contains the command line options of the game.
+use crate::backend::error::RLError;
+use crate::RLResult;
+
+/// The options the game was started with
+/// * `--seed <number>` - Starts new games with the given seed instead of a random one
+/// * `--record` - Records the input of every game into the recordings folder
+/// * `--replay <file>` - Replays a recording instead of showing the main menu
+/// * `--bot` - Lets the `ScriptedAgent` play new games instead of the keyboard
+/// * `--headless` - Replays the recording or lets the bot play a game without a window
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The seed of new games, chosen randomly for every new game if it is `None`
+    pub seed: Option<u64>,
+    /// Whether the input of every game is recorded
+    pub(crate) record: bool,
+    /// The path of the recording to replay
+    pub replay: Option<String>,
+    /// Whether new games are played by the `ScriptedAgent`
+    pub bot: bool,
+    /// Whether the recording is replayed or the bot plays without a window
+    pub headless: bool,
+}
+
+impl Options {
+    /// Reads the options from the command line arguments
+    /// # Arguments
+    /// * `args` - The command line arguments without the program name
+    /// # Returns
+    /// * `RLResult<Options>` - The options or an error if an argument is unknown or invalid
+    pub fn parse(mut args: impl Iterator<Item = String>) -> RLResult<Self> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let seed = args.next().unwrap_or_default();
+                    options.seed = Some(seed.parse().map_err(|_| {
+                        RLError::InitError(format!("--seed needs a number, got \"{seed}\""))
+                    })?);
+                }
+                "--record" => options.record = true,
+                "--replay" => {
+                    options.replay = Some(args.next().ok_or_else(|| {
+                        RLError::InitError("--replay needs the path of a recording".to_string())
+                    })?);
+                }
+                "--bot" => options.bot = true,
+                "--headless" => options.headless = true,
+                _ => return Err(RLError::InitError(format!("Unknown argument \"{arg}\""))),
+            }
+        }
+        if options.headless && options.replay.is_none() && !options.bot {
+            return Err(RLError::InitError(
+                "--headless can only be used with --replay or --bot".to_string(),
+            ));
+        }
+        if options.bot && options.replay.is_some() {
+            return Err(RLError::InitError(
+                "--bot can not be used with --replay".to_string(),
+            ));
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> RLResult<Options> {
+        Options::parse(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = parse(&["--seed", "42", "--record"]).unwrap();
+        assert_eq!(options.seed, Some(42));
+        assert!(options.record);
+        let options = parse(&["--replay", "run.yaml", "--headless"]).unwrap();
+        assert_eq!(options.replay.as_deref(), Some("run.yaml"));
+        assert!(options.headless);
+        let options = parse(&["--bot", "--headless", "--seed", "3"]).unwrap();
+        assert!(options.bot && options.headless);
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(parse(&["--seed", "comet"]).is_err());
+        assert!(parse(&["--replay"]).is_err());
+        assert!(parse(&["--headless"]).is_err());
+        assert!(parse(&["--bot", "--replay", "run.yaml"]).is_err());
+        assert!(parse(&["--fast"]).is_err());
+    }
+}
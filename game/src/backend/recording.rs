@@ -0,0 +1,259 @@
+//! Contains the recording of play sessions and their deterministic replay.
+use crate::backend::constants::{DEFAULT_LEVEL_PATH, RECORDINGS_PATH, RECORDING_VERSION};
+use crate::backend::error::create_data_error;
+use crate::backend::game_data::GameData;
+use crate::backend::gamestate::GameState;
+use crate::backend::movement::Input;
+use crate::backend::simulation::Simulation;
+use crate::backend::utils::checksum;
+use crate::RLResult;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tracing::info;
+
+/// A recorded play session. It contains the game state the session started with and the input
+/// of every tick, so replaying it leads to exactly the same game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// The version of the recording format
+    version: u32,
+    /// The seed of the recorded game
+    pub seed: u64,
+    /// The save of the game when the recording started
+    start: serde_yaml::Value,
+    /// The input of every tick, consecutive ticks with the same input are stored together
+    inputs: Vec<InputRun>,
+    /// The checksum of the save of the game when the recording ended
+    pub(crate) checksum: u64,
+}
+
+/// The same input for several consecutive ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct InputRun {
+    ticks: u32,
+    #[serde(flatten)]
+    input: Input,
+}
+
+/// Records the input of a running game
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    recording: Recording,
+}
+
+/// Returns the input of a recording tick by tick
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    inputs: Vec<InputRun>,
+    /// The index of the current run
+    run: usize,
+    /// The ticks already replayed of the current run
+    tick: u32,
+    /// The checksum the game has to have after the last tick
+    pub(crate) checksum: u64,
+}
+
+/// Returns the checksum of the save of a game state
+/// # Arguments
+/// * `state` - The game state
+pub(crate) fn state_checksum(state: &GameState) -> RLResult<u64> {
+    Ok(checksum(serde_yaml::to_string(state)?.as_bytes()))
+}
+
+impl Recorder {
+    /// Starts recording a game, this has to be called before the first tick is recorded
+    /// # Arguments
+    /// * `state` - The game state when the recording starts
+    pub(crate) fn new(state: &GameState) -> RLResult<Self> {
+        info!("Started recording with seed {}", state.rng.seed());
+        Ok(Self {
+            recording: Recording {
+                version: RECORDING_VERSION,
+                seed: state.rng.seed(),
+                start: serde_yaml::to_value(state)?,
+                inputs: vec![],
+                checksum: 0,
+            },
+        })
+    }
+
+    /// Records the input of one tick
+    /// # Arguments
+    /// * `input` - The input of the player in this tick
+    pub(crate) fn record(&mut self, input: Input) {
+        match self.recording.inputs.last_mut() {
+            Some(run) if run.input == input => run.ticks += 1,
+            _ => self.recording.inputs.push(InputRun { ticks: 1, input }),
+        }
+    }
+
+    /// Ends the recording
+    /// # Arguments
+    /// * `state` - The game state after the last recorded tick
+    /// # Returns
+    /// * `RLResult<Recording>` - The finished recording
+    pub(crate) fn finish(self, state: &GameState) -> RLResult<Recording> {
+        let mut recording = self.recording;
+        recording.checksum = state_checksum(state)?;
+        Ok(recording)
+    }
+}
+
+impl Recording {
+    /// Loads a recording from a file
+    /// # Arguments
+    /// * `path` - The path of the recording
+    /// # Returns
+    /// * `RLResult<Recording>` - The recording or an error if it has an unsupported version
+    pub fn load(path: &str) -> RLResult<Self> {
+        info!("Loading recording from {}", path);
+        let source = fs::read_to_string(path).map_err(|e| create_data_error(path, "file", e))?;
+        let recording: Recording =
+            serde_yaml::from_str(&source).map_err(|e| create_data_error(path, "recording", e))?;
+        if recording.version != RECORDING_VERSION {
+            return Err(create_data_error(
+                path,
+                "version",
+                format!(
+                    "recordings of version {} can not be replayed, only version {RECORDING_VERSION}",
+                    recording.version
+                ),
+            ));
+        }
+        Ok(recording)
+    }
+
+    /// Writes the recording into a new file in the recordings folder
+    /// # Returns
+    /// * `RLResult<String>` - The path of the written file
+    pub(crate) fn save(&self) -> RLResult<String> {
+        fs::create_dir_all(RECORDINGS_PATH)?;
+        let path = format!(
+            "{RECORDINGS_PATH}/RL-{}.yaml",
+            Local::now().format("%Y-%m-%d_%H-%M-%S")
+        );
+        fs::write(&path, serde_yaml::to_string(self)?)?;
+        info!("Saved recording to {}", path);
+        Ok(path)
+    }
+
+    /// Returns the game state the recording started with. Its data files are not loaded yet.
+    pub fn start_state(&self) -> RLResult<GameState> {
+        Ok(serde_yaml::from_value(self.start.clone())?)
+    }
+
+    /// Returns the path of the level the recorded game is played in
+    pub fn level_path(&self) -> RLResult<String> {
+        Ok(self
+            .start_state()?
+            .level_path
+            .unwrap_or_else(|| DEFAULT_LEVEL_PATH.to_string()))
+    }
+
+    /// Returns a `Replayer` which returns the recorded input tick by tick
+    pub fn replayer(&self) -> Replayer {
+        Replayer {
+            inputs: self.inputs.clone(),
+            run: 0,
+            tick: 0,
+            checksum: self.checksum,
+        }
+    }
+
+    /// Replays the recording without a window and checks if the game ends the same way.
+    /// # Arguments
+    /// * `data` - The data files the recorded game was played with
+    /// # Returns
+    /// * `RLResult<Simulation>` - The replayed game or an error if its checksum does not match
+    pub fn replay(&self, data: GameData) -> RLResult<Simulation> {
+        let mut state = self.start_state()?;
+        state.set_data(data);
+        let mut simulation = Simulation::from_state(state);
+        for input in self.replayer() {
+            simulation.step(input)?;
+        }
+        let checksum = state_checksum(simulation.state())?;
+        if checksum != self.checksum {
+            return Err(create_data_error(
+                "recording",
+                "checksum",
+                format!(
+                    "the replay ended with checksum {checksum}, but {} was recorded",
+                    self.checksum
+                ),
+            ));
+        }
+        info!("Replay matches the recording");
+        Ok(simulation)
+    }
+}
+
+impl Replayer {
+    /// Returns whether all recorded input was returned
+    pub(crate) fn is_finished(&self) -> bool {
+        self.run >= self.inputs.len()
+    }
+}
+
+impl Iterator for Replayer {
+    type Item = Input;
+
+    fn next(&mut self) -> Option<Input> {
+        let run = self.inputs.get(self.run)?;
+        self.tick += 1;
+        if self.tick >= run.ticks {
+            self.run += 1;
+            self.tick = 0;
+        }
+        Some(run.input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::screen::Screen;
+    use std::sync::mpsc::channel;
+
+    /// Plays a short game and records it
+    fn record_game() -> Recording {
+        let mut state = GameState::new_game(GameData::bundled(), 11).unwrap();
+        state.disable_saves = true;
+        let mut recorder = Recorder::new(&state).unwrap();
+        let (sender, _receiver) = channel();
+        state.set_sender(sender);
+        let right = Input {
+            right: true,
+            ..Input::default()
+        };
+        for i in 0..3000 {
+            let input = if i % 300 < 20 {
+                right
+            } else {
+                Input::default()
+            };
+            recorder.record(input);
+            state.step(input).unwrap();
+        }
+        recorder.finish(&state).unwrap()
+    }
+
+    #[test]
+    fn test_replay_matches_recording() {
+        let recording = record_game();
+        assert_eq!(recording.inputs.len(), 20);
+        let source = serde_yaml::to_string(&recording).unwrap();
+        let loaded: Recording = serde_yaml::from_str(&source).unwrap();
+        assert_eq!(loaded, recording);
+        let simulation = loaded.replay(GameData::bundled()).unwrap();
+        assert_eq!(simulation.state().player.time, 3000);
+    }
+
+    #[test]
+    fn test_changed_recording_does_not_match() {
+        let mut recording = record_game();
+        recording.inputs[0].input.down = true;
+        assert!(recording.replay(GameData::bundled()).is_err());
+    }
+}
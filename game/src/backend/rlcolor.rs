@@ -104,4 +104,11 @@ impl RLColor {
         b: 0.,
         a: 1.,
     };
+    /// Color: transparent black (darkens the game below a menu)
+    pub const SHADOW: Color = Color {
+        r: 0.,
+        g: 0.,
+        b: 0.,
+        a: 0.6,
+    };
 }
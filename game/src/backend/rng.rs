@@ -0,0 +1,103 @@
+//! Contains the seeded random number generator of a game, which is stored in the save file.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::RangeBounds;
+
+/// The random number generator of a game. All random decisions of a game are made by it,
+/// so a game started with the same seed and the same inputs plays out the same way.
+pub(crate) struct GameRng {
+    /// The seed the game was started with, used to replay the game
+    seed: u64,
+    rng: fastrand::Rng,
+}
+
+/// The serialized form of the `GameRng`
+#[derive(Serialize, Deserialize)]
+struct RngState {
+    seed: u64,
+    /// The current internal state of the generator
+    state: u64,
+}
+
+impl GameRng {
+    /// Creates a new random number generator
+    /// # Arguments
+    /// * `seed` - The seed of the game
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: fastrand::Rng::with_seed(seed),
+        }
+    }
+
+    /// Returns the seed the game was started with
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns a random number in the given range
+    /// # Arguments
+    /// * `range` - The range of the number, it must not be empty
+    pub(crate) fn usize(&self, range: impl RangeBounds<usize>) -> usize {
+        self.rng.usize(range)
+    }
+}
+
+impl Default for GameRng {
+    /// Creates a random number generator with a random seed
+    fn default() -> Self {
+        Self::new(fastrand::u64(..))
+    }
+}
+
+impl std::fmt::Debug for GameRng {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GameRng")
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serialize for GameRng {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RngState {
+            seed: self.seed,
+            state: self.rng.get_seed(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameRng {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = RngState::deserialize(deserializer)?;
+        Ok(Self {
+            seed: state.seed,
+            rng: fastrand::Rng::with_seed(state.state),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        let first = GameRng::new(42);
+        let second = GameRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(first.usize(..1000), second.usize(..1000));
+        }
+    }
+
+    #[test]
+    fn test_save_keeps_state() {
+        let rng = GameRng::new(7);
+        rng.usize(..10);
+        let loaded: GameRng = serde_yaml::from_str(&serde_yaml::to_string(&rng).unwrap()).unwrap();
+        assert_eq!(loaded.seed(), 7);
+        for _ in 0..100 {
+            assert_eq!(rng.usize(..1000), loaded.usize(..1000));
+        }
+    }
+}
@@ -0,0 +1,630 @@
+//! Contains the versioned format of the save files and the migrations which upgrade older saves.
+//! Save files start with a checksum of their content and are written atomically, so a crash while
+//! saving never damages the existing save.
+use crate::backend::constants::SAVE_VERSION;
+use crate::backend::error::{create_data_error, RLError};
+use crate::backend::gamestate::GameState;
+use crate::backend::utils::checksum;
+use crate::RLResult;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use tracing::{info, warn};
+
+/// A migration upgrades the game of a save by one version
+type Migration = fn(&mut Value, &str) -> RLResult;
+
+/// Contains the migrations of all older versions, the migration at index `n` upgrades a save
+/// from version `n` to version `n + 1`. Once released, a migration must never change.
+const MIGRATIONS: [Migration; SAVE_VERSION as usize] =
+    [migrate_v0, migrate_v1, migrate_v2, migrate_v3];
+
+/// The header of a save, followed by the game itself
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u32,
+    game: &'a GameState,
+}
+
+/// The first line of a save file contains the checksum of the rest of the file
+const CHECKSUM_PREFIX: &str = "checksum: ";
+
+/// Turns a game into the content of a save file of the current version
+/// # Arguments
+/// * `state` - The game to save
+/// # Returns
+/// * `RLResult<String>` - The content of the save file
+pub fn serialize(state: &GameState) -> RLResult<String> {
+    let content = serde_yaml::to_string(&SaveFile {
+        version: SAVE_VERSION,
+        game: state,
+    })?;
+    Ok(format!(
+        "{CHECKSUM_PREFIX}{}\n{content}",
+        checksum(content.as_bytes())
+    ))
+}
+
+/// Reads the content of a save file of any known version.
+/// Saves written before the header existed are read as version 0, saves without a checksum are
+/// read without verifying it.
+/// # Arguments
+/// * `source` - The content of the save file
+/// * `path` - The path of the save file, used in error messages
+/// # Returns
+/// * `RLResult<GameState>` - The saved game, upgraded to the current version
+pub fn deserialize(source: &str, path: &str) -> RLResult<GameState> {
+    let mut source = source;
+    if let Some(checked) = source.strip_prefix(CHECKSUM_PREFIX) {
+        let (expected, content) = checked.split_once('\n').unwrap_or((checked, ""));
+        if expected.trim().parse() != Ok(checksum(content.as_bytes())) {
+            return Err(create_data_error(
+                path,
+                "checksum",
+                "does not match the content, the save is damaged",
+            ));
+        }
+        source = content;
+    }
+    let document: Value = serde_yaml::from_str(source)?;
+    let (version, game) = match document {
+        Value::Mapping(mut mapping) if mapping.contains_key("version") => {
+            let version = mapping
+                .get("version")
+                .and_then(Value::as_u64)
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| create_data_error(path, "version", "must be a number"))?;
+            let game = mapping
+                .remove("game")
+                .ok_or_else(|| create_data_error(path, "game", "is missing"))?;
+            (version, game)
+        }
+        document => (0, document),
+    };
+    upgrade(version, game, path)
+}
+
+/// Upgrades a saved game step by step from its version to the current version and reads it
+/// # Arguments
+/// * `version` - The version the game was saved with
+/// * `game` - The saved game
+/// * `path` - The path of the save file, used in error messages
+/// # Returns
+/// * `RLResult<GameState>` - The saved game or an error if it is invalid or of a newer version
+pub(crate) fn upgrade(version: u32, mut game: Value, path: &str) -> RLResult<GameState> {
+    if version > SAVE_VERSION {
+        return Err(create_data_error(
+            path,
+            "version",
+            format!("saves of version {version} are newer than this game (version {SAVE_VERSION})"),
+        ));
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        info!("Upgrading save {} from version {}", path, from);
+        migration(&mut game, path)?;
+    }
+    serde_yaml::from_value(game).map_err(|e| create_data_error(path, "game", e))
+}
+
+/// Writes a file atomically. The content is written to a temporary file first, which then
+/// replaces the file, so the file is either the old or the new file even if the game crashes.
+/// # Arguments
+/// * `path` - The path of the file
+/// * `content` - The new content of the file
+pub fn write_atomic(path: &str, content: &str) -> RLResult {
+    let temp_path = format!("{path}.tmp");
+    let mut file = File::create(&temp_path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Returns the path of a backup of a save file, backup 0 is the save file itself
+/// # Arguments
+/// * `path` - The path of the save file
+/// * `backup` - The number of the backup, higher numbers are older
+pub(crate) fn backup_path(path: &str, backup: usize) -> String {
+    if backup == 0 {
+        path.to_string()
+    } else {
+        format!("{path}.{backup}")
+    }
+}
+
+/// Moves a save file and its backups one place back to make room for a new save,
+/// the oldest backup is dropped
+/// # Arguments
+/// * `path` - The path of the save file
+/// * `backups` - The number of backups which are kept
+pub(crate) fn rotate_backups(path: &str, backups: usize) -> RLResult {
+    for backup in (0..backups).rev() {
+        match fs::rename(backup_path(path, backup), backup_path(path, backup + 1)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Reads the newest valid game of a save file and its backups.
+/// Missing and damaged files are skipped, so a crash while saving never loses the whole game.
+/// # Arguments
+/// * `path` - The path of the save file
+/// * `backups` - The number of backups which are kept
+/// # Returns
+/// * `RLResult<(GameState, bool)>` - The game and whether a backup was loaded instead of the save
+/// file, or the error of the save file if no file is valid
+pub(crate) fn read_with_backups(path: &str, backups: usize) -> RLResult<(GameState, bool)> {
+    // A missing file is only reported if no file is damaged
+    let mut missing = None;
+    let mut damaged = None;
+    for backup in 0..=backups {
+        let backup_path = backup_path(path, backup);
+        let result = fs::read_to_string(&backup_path)
+            .map_err(RLError::from)
+            .and_then(|source| deserialize(&source, &backup_path));
+        match result {
+            Ok(state) => {
+                if backup > 0 {
+                    warn!("Loaded backup {} instead of {}", backup_path, path);
+                }
+                return Ok((state, backup > 0));
+            }
+            Err(RLError::IO(e)) if e.kind() == ErrorKind::NotFound => {
+                missing.get_or_insert(RLError::IO(e));
+            }
+            Err(e) => {
+                warn!("Skipping damaged save {}: {:?}", backup_path, e);
+                damaged.get_or_insert(e);
+            }
+        }
+    }
+    Err(damaged
+        .or(missing)
+        .unwrap_or_else(|| create_data_error(path, "save", "no save exists")))
+}
+
+/// Returns the id of an item of version 0, which stored the whole item instead of its id
+/// # Arguments
+/// * `name` - The name of the item in version 0
+fn item_id_v0(name: &str) -> Option<&'static str> {
+    match name {
+        "SuperGlue" => Some("super_glue"),
+        "Benzin" => Some("benzin"),
+        "3D-gedrucktes-Teil" => Some("printed_part"),
+        _ => None,
+    }
+}
+
+/// Replaces the items in a list of items and amounts by their ids, items which already are ids
+/// are kept
+/// # Arguments
+/// * `items` - The list of items and amounts
+/// * `path` - The path of the save file, used in error messages
+/// * `field` - The path of the list inside the save, used in error messages
+fn items_to_ids_v0(items: &mut Value, path: &str, field: &str) -> RLResult {
+    let Value::Sequence(items) = items else {
+        return Ok(());
+    };
+    for entry in items {
+        let Some(item) = entry.get_mut(0) else {
+            continue;
+        };
+        if let Value::Mapping(mapping) = item {
+            let name = mapping.get("name").and_then(Value::as_str).unwrap_or("");
+            let id = item_id_v0(name)
+                .ok_or_else(|| create_data_error(path, field, format!("unknown item {name}")))?;
+            *item = Value::from(id);
+        }
+    }
+    Ok(())
+}
+
+/// Upgrades a save from version 0 to version 1.
+/// Items are referenced by their id and the progress in the campaign is stored as objectives
+/// instead of only the milestone.
+/// # Arguments
+/// * `game` - The saved game
+/// * `path` - The path of the save file, used in error messages
+fn migrate_v0(game: &mut Value, path: &str) -> RLResult {
+    if let Some(inventory) = game
+        .get_mut("player")
+        .and_then(|player| player.get_mut("inventory"))
+    {
+        items_to_ids_v0(inventory, path, "player.inventory")?;
+    }
+    if let Some(Value::Sequence(machines)) = game.get_mut("machines") {
+        for machine in machines {
+            if let Some(Value::Sequence(trades)) = machine.get_mut("trades") {
+                for cost in trades.iter_mut().filter_map(|trade| trade.get_mut("cost")) {
+                    items_to_ids_v0(cost, path, "machines.trades.cost")?;
+                }
+            }
+            if let Some(cost) = machine
+                .get_mut("last_trade")
+                .and_then(|trade| trade.get_mut("cost"))
+            {
+                items_to_ids_v0(cost, path, "machines.last_trade.cost")?;
+            }
+        }
+    }
+    let Value::Mapping(game) = game else {
+        return Err(create_data_error(path, "game", "must be a mapping"));
+    };
+    if !game.contains_key("objectives") {
+        let player = game.get("player");
+        let milestone = player
+            .and_then(|player| player.get("milestone"))
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        let time = player
+            .and_then(|player| player.get("time"))
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        game.insert("objectives".into(), objectives_v0(milestone, time));
+    }
+    Ok(())
+}
+
+/// Returns the progress in the campaign of version 0, which only knew the milestones
+/// # Arguments
+/// * `milestone` - The milestone the player had reached
+/// * `time` - The time of the save, the active objective counts as activated at this time
+fn objectives_v0(milestone: u64, time: u64) -> Value {
+    let (completed, active): (&[&str], Option<&str>) = match milestone {
+        0 => (&[], None),
+        1 => (&["landing"], Some("life_support")),
+        _ => (&["landing", "life_support"], Some("rescue")),
+    };
+    let mut objectives = Mapping::new();
+    objectives.insert(
+        "active".into(),
+        active
+            .map(|id| vec![Value::Sequence(vec![id.into(), time.into()])])
+            .unwrap_or_default()
+            .into(),
+    );
+    objectives.insert(
+        "completed".into(),
+        completed.iter().map(|id| Value::from(*id)).collect(),
+    );
+    objectives.insert("completed_trades".into(), Value::Sequence(vec![]));
+    objectives.insert("survived_events".into(), Value::Sequence(vec![]));
+    Value::Mapping(objectives)
+}
+
+/// Upgrades a save from version 1 to version 2.
+/// The oxygen running machines add to or take from their room is simulated by the atmosphere of the
+/// room, so the change of the oxygen of the player only contains the breathing of the player.
+/// # Arguments
+/// * `game` - The saved game
+/// * `_path` - The path of the save file, used in error messages
+#[allow(clippy::unnecessary_wraps)] // Every migration has the signature of a `Migration`
+fn migrate_v1(game: &mut Value, _path: &str) -> RLResult {
+    let machine_oxygen: i64 = match game.get("machines") {
+        Some(Value::Sequence(machines)) => machines
+            .iter()
+            .filter(|machine| machine.get("state").and_then(Value::as_str) == Some("Running"))
+            .filter_map(|machine| machine.get("running_resources")?.get("oxygen")?.as_i64())
+            .sum(),
+        _ => 0,
+    };
+    if let Some(oxygen) = game
+        .get_mut("player")
+        .and_then(|player| player.get_mut("resources_change"))
+        .and_then(|change| change.get_mut("oxygen"))
+    {
+        *oxygen = Value::from(oxygen.as_i64().unwrap_or(0) - machine_oxygen);
+    }
+    Ok(())
+}
+
+/// Upgrades a save from version 2 to version 3.
+/// The production trades, which start a running machine and give items to the player once they
+/// are finished, become recipes with separate inputs and outputs.
+/// # Arguments
+/// * `game` - The saved game
+/// * `_path` - The path of the save file, used in error messages
+#[allow(clippy::unnecessary_wraps)] // Every migration has the signature of a `Migration`
+fn migrate_v2(game: &mut Value, _path: &str) -> RLResult {
+    let Some(Value::Sequence(machines)) = game.get_mut("machines") else {
+        return Ok(());
+    };
+    for machine in machines {
+        let Some(Value::Sequence(trades)) = machine.get_mut("trades") else {
+            continue;
+        };
+        let (production, others): (Vec<Value>, Vec<Value>) =
+            trades.drain(..).partition(is_production_v2);
+        *trades = others;
+        if production.is_empty() {
+            continue;
+        }
+        let recipes = production.iter().map(recipe_v2).collect();
+        if let Value::Mapping(machine) = machine {
+            machine.insert("recipes".into(), Value::Sequence(recipes));
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether a trade of version 2 produces items while the machine is running
+/// # Arguments
+/// * `trade` - The saved trade
+fn is_production_v2(trade: &Value) -> bool {
+    let amounts = cost_v2(trade);
+    trade.get("initial_state").and_then(Value::as_str) == Some("Idle")
+        && trade.get("resulting_state").and_then(Value::as_str) == Some("Running")
+        && trade.get("return_after_timer").and_then(Value::as_bool) == Some(true)
+        && amounts.iter().any(|(_, amount)| *amount < 0)
+}
+
+/// Returns the item ids and amounts of the cost of a trade of version 2
+/// # Arguments
+/// * `trade` - The saved trade
+fn cost_v2(trade: &Value) -> Vec<(String, i64)> {
+    let Some(Value::Sequence(cost)) = trade.get("cost") else {
+        return vec![];
+    };
+    cost.iter()
+        .filter_map(|entry| {
+            let id = entry.get(0)?.as_str()?;
+            Some((id.to_string(), entry.get(1)?.as_i64()?))
+        })
+        .collect()
+}
+
+/// Converts a production trade of version 2 into a recipe
+/// # Arguments
+/// * `trade` - The saved trade
+fn recipe_v2(trade: &Value) -> Value {
+    let amounts = cost_v2(trade);
+    let list = |sign: i64| {
+        amounts
+            .iter()
+            .filter(|(_, amount)| amount * sign > 0)
+            .map(|(id, amount)| Value::Sequence(vec![id.as_str().into(), (amount * sign).into()]))
+            .collect::<Vec<Value>>()
+    };
+    let mut recipe = Mapping::new();
+    recipe.insert(
+        "name".into(),
+        trade.get("name").cloned().unwrap_or_default(),
+    );
+    recipe.insert("inputs".into(), Value::Sequence(list(1)));
+    recipe.insert("outputs".into(), Value::Sequence(list(-1)));
+    recipe.insert(
+        "time_ticks".into(),
+        trade.get("time_ticks").cloned().unwrap_or(Value::from(1)),
+    );
+    Value::Mapping(recipe)
+}
+
+/// Upgrades a save from version 3 to version 4.
+/// The objectives only remember the last time each trade finished and each event ended instead of
+/// every time, so the save does not grow the longer the game is played.
+/// # Arguments
+/// * `game` - The saved game
+/// * `_path` - The path of the save file, used in error messages
+#[allow(clippy::unnecessary_wraps)] // Every migration has the signature of a `Migration`
+fn migrate_v3(game: &mut Value, _path: &str) -> RLResult {
+    let Some(Value::Mapping(objectives)) = game.get_mut("objectives") else {
+        return Ok(());
+    };
+    for field in ["completed_trades", "survived_events"] {
+        let Some(Value::Sequence(log)) = objectives.get(field) else {
+            continue;
+        };
+        let mut last = Mapping::new();
+        for entry in log {
+            let (Some(name), Some(time)) = (entry.get(0), entry.get(1).and_then(Value::as_u64))
+            else {
+                continue;
+            };
+            let previous = last.get(name).and_then(Value::as_u64).unwrap_or(0);
+            last.insert(name.clone(), previous.max(time).into());
+        }
+        objectives.insert(field.into(), Value::Mapping(last));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::machines::machine::State;
+    use std::path::Path;
+
+    #[test]
+    fn test_upgrade_v0_save() {
+        let state = deserialize(include_str!("../../test-saves/v0.yaml"), "v0.yaml").unwrap();
+        assert_eq!(state.player.milestone, 1);
+        assert_eq!(state.player.time, 5400);
+        assert_eq!(
+            state.player.inventory,
+            vec![
+                ("super_glue".to_string(), 2),
+                ("benzin".to_string(), 3),
+                ("printed_part".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            state.machines[0].trades[0].cost[0],
+            ("super_glue".to_string(), 2)
+        );
+        assert_eq!(state.machines[0].state, State::Idle);
+        assert_eq!(state.objectives.completed, vec!["landing".to_string()]);
+        assert_eq!(
+            state.objectives.active,
+            vec![("life_support".to_string(), 5400)]
+        );
+    }
+
+    #[test]
+    fn test_upgrade_v0_test_saves() {
+        for source in [
+            include_str!("../../test-saves/comms_repaired_autosave.yaml"),
+            include_str!("../../test-saves/death_autosave.yaml"),
+            include_str!("../../test-saves/machines_repaired_autosave.yaml"),
+        ] {
+            let state = deserialize(source, "v0").unwrap();
+            assert_eq!(state.objectives.is_started(), state.player.milestone > 0);
+        }
+    }
+
+    #[test]
+    fn test_read_v1_save() {
+        let state = deserialize(include_str!("../../test-saves/v1.yaml"), "v1.yaml").unwrap();
+        assert_eq!(state.player.milestone, 1);
+        assert_eq!(state.player.time, 5400);
+        assert_eq!(state.objectives.completed, vec!["landing".to_string()]);
+        // Saving the game again does not change it
+        let saved = serialize(&state).unwrap();
+        assert_eq!(deserialize(&saved, "v1.yaml").unwrap().player, state.player);
+    }
+
+    #[test]
+    fn test_upgrade_v1_recipes() {
+        let state = deserialize(include_str!("../../test-saves/v1.yaml"), "v1.yaml").unwrap();
+        let printer = state
+            .machines
+            .iter()
+            .find(|machine| machine.name == "3D-Drucker")
+            .unwrap();
+        assert!(printer
+            .trades
+            .iter()
+            .all(|trade| trade.name != "produce_3d_teil"));
+        assert_eq!(printer.recipes[0].name, "produce_3d_teil");
+        assert_eq!(
+            printer.recipes[0].inputs,
+            vec![("super_glue".to_string(), 2)]
+        );
+        assert_eq!(
+            printer.recipes[0].outputs,
+            vec![("printed_part".to_string(), 1)]
+        );
+        // Trades which do not produce anything stay trades
+        let communication = state
+            .machines
+            .iter()
+            .find(|machine| machine.name == "Kommunikationsmodul")
+            .unwrap();
+        assert_eq!(communication.trades.len(), 2);
+        assert!(communication.recipes.is_empty());
+    }
+
+    #[test]
+    fn test_read_v2_save() {
+        let state = deserialize(include_str!("../../test-saves/v2.yaml"), "v2.yaml").unwrap();
+        // The oxygen change of a save which already simulates the air is not changed again
+        assert_eq!(state.player.resources_change.oxygen, -5);
+        assert_eq!(state.player.resources_change.energy, -45);
+        assert_eq!(state.machines[0].state, State::Running);
+        assert_eq!(state.machines[3].recipes[0].name, "produce_3d_teil");
+    }
+
+    #[test]
+    fn test_read_v3_save() {
+        let state = deserialize(include_str!("../../test-saves/v3.yaml"), "v3.yaml").unwrap();
+        assert_eq!(state.player.resources_change.oxygen, -5);
+        assert!((state.machines[0].get_durability_percentage() - 0.64).abs() < 1e-6);
+        assert!(state.machines[0].wears_down());
+    }
+
+    #[test]
+    fn test_upgrade_v3_history() {
+        let state = deserialize(include_str!("../../test-saves/v3.yaml"), "v3.yaml").unwrap();
+        // Only the last time of every trade and event is kept
+        let objectives = &state.objectives;
+        assert_eq!(objectives.completed_trades.len(), 2);
+        assert_eq!(objectives.completed_trades["fueling_Stromgenerator"], 5200);
+        assert_eq!(objectives.survived_events["Sandsturm"], 5100);
+    }
+
+    #[test]
+    fn test_fixture_of_every_version() {
+        // Bumping the save version needs a fixture of the new version in `test-saves`
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test-saves");
+        for version in 0..=SAVE_VERSION {
+            let path = dir.join(format!("v{version}.yaml"));
+            let source = fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("Missing fixture {}: {e}", path.display()));
+            if version > 0 {
+                assert!(source.starts_with(&format!("version: {version}\n")));
+            }
+            deserialize(&source, &path.to_string_lossy()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_upgrade_v1_oxygen() {
+        let mut game: Value = serde_yaml::from_str(
+            "player:
+  resources_change: { oxygen: 10 }
+machines:
+  - { state: Running, running_resources: { oxygen: 30 } }
+  - { state: Running, running_resources: { oxygen: -15 } }
+  - { state: Idle, running_resources: { oxygen: -15 } }
+",
+        )
+        .unwrap();
+        migrate_v1(&mut game, "v1.yaml").unwrap();
+        // The running oxygen generator and hole only change the air of their rooms
+        assert_eq!(
+            game["player"]["resources_change"]["oxygen"],
+            Value::from(-5)
+        );
+    }
+
+    #[test]
+    fn test_detect_damaged_save() {
+        let mut state = GameState::default();
+        state.player.time = 1234;
+        let saved = serialize(&state).unwrap();
+        assert_eq!(deserialize(&saved, "save").unwrap().player.time, 1234);
+        let damaged = saved.replace("time: 1234", "time: 4321");
+        assert!(matches!(
+            deserialize(&damaged, "save"),
+            Err(RLError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_fall_back_to_backup() {
+        // Every test binary uses its own folder, so tests running at the same time do not clash
+        let dir = std::env::temp_dir().join(format!("red-life-backups-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("autosave.yaml").to_string_lossy().to_string();
+        assert!(matches!(
+            read_with_backups(&path, 3),
+            Err(RLError::IO(e)) if e.kind() == ErrorKind::NotFound
+        ));
+        let mut state = GameState::default();
+        for time in [1, 2, 3, 4, 5] {
+            state.player.time = time;
+            rotate_backups(&path, 3).unwrap();
+            write_atomic(&path, &serialize(&state).unwrap()).unwrap();
+        }
+        // Only the newest saves are kept
+        assert!(!Path::new(&backup_path(&path, 4)).exists());
+        let (loaded, restored) = read_with_backups(&path, 3).unwrap();
+        assert_eq!((loaded.player.time, restored), (5, false));
+        // A save which was cut off while writing is skipped
+        let source = fs::read_to_string(&path).unwrap();
+        fs::write(&path, &source[..source.len() / 2]).unwrap();
+        let (loaded, restored) = read_with_backups(&path, 3).unwrap();
+        assert_eq!((loaded.player.time, restored), (4, true));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reject_newer_save() {
+        let source = format!("version: {}\ngame: {{}}\n", SAVE_VERSION + 1);
+        assert!(deserialize(&source, "new.yaml").is_err());
+    }
+}
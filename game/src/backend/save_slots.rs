@@ -0,0 +1,191 @@
+//! Contains the save slots, in which the player keeps games under a name next to the autosave.
+use crate::backend::constants::{SAVE_SLOTS, SAVE_VERSION};
+use crate::backend::error::create_data_error;
+use crate::backend::gamestate::GameState;
+use crate::backend::save_file;
+use crate::{tr, RLResult};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tracing::info;
+
+/// Describes the game saved in a slot, it is shown in the list of slots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SlotMetadata {
+    /// The name of the save, which the player can change
+    pub(crate) name: String,
+    /// When the game was saved, in local time
+    pub(crate) saved_at: String,
+    /// The time the game was played in ticks
+    pub(crate) play_time: u32,
+    /// The milestone the player had reached
+    pub(crate) milestone: usize,
+    #[serde(default)]
+    /// A short description of the state of the player, slots saved before it existed have none
+    pub(crate) description: String,
+}
+
+/// A game saved in a slot together with its metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SlotFile {
+    pub(crate) metadata: SlotMetadata,
+    #[serde(default)]
+    /// The version of the save format the game was saved with
+    version: u32,
+    /// The save of the game
+    game: serde_yaml::Value,
+}
+
+/// Only the metadata of a slot file, used to list the slots without reading the games
+#[derive(Deserialize)]
+struct SlotHeader {
+    metadata: SlotMetadata,
+}
+
+/// Returns the path of the file of a slot
+/// # Arguments
+/// * `dir` - The folder of the slots
+/// * `slot` - The index of the slot
+fn slot_path(dir: &str, slot: usize) -> String {
+    format!("{dir}/slot-{}.yaml", slot + 1)
+}
+
+/// Describes the resources of the player in the current language
+/// # Arguments
+/// * `state` - The saved game
+fn describe(state: &GameState) -> String {
+    let percent = |value: u16| u32::from(value) * 100 / u32::from(u16::MAX);
+    let resources = &state.player.resources;
+    tr!(
+        "slots.description",
+        oxygen = percent(resources.oxygen),
+        energy = percent(resources.energy),
+        life = percent(resources.life)
+    )
+}
+
+impl SlotFile {
+    /// Saves a game into a new slot file, which is not written yet
+    /// # Arguments
+    /// * `state` - The game to save
+    /// * `name` - The name of the save
+    pub(crate) fn new(state: &GameState, name: String) -> RLResult<Self> {
+        Ok(Self {
+            metadata: SlotMetadata {
+                name,
+                saved_at: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+                play_time: state.player.time,
+                milestone: state.player.milestone,
+                description: describe(state),
+            },
+            version: SAVE_VERSION,
+            game: serde_yaml::to_value(state)?,
+        })
+    }
+
+    /// Reads the file of a slot
+    /// # Arguments
+    /// * `dir` - The folder of the slots
+    /// * `slot` - The index of the slot
+    /// # Returns
+    /// * `RLResult<SlotFile>` - The slot file or an error if the slot is empty or invalid
+    pub(crate) fn read(dir: &str, slot: usize) -> RLResult<Self> {
+        let path = slot_path(dir, slot);
+        info!("Loading save slot {}", path);
+        let source = fs::read_to_string(&path)?;
+        serde_yaml::from_str(&source).map_err(|e| create_data_error(&path, "slot", e))
+    }
+
+    /// Writes the file of a slot, a game saved in the slot before is overwritten
+    /// # Arguments
+    /// * `dir` - The folder of the slots
+    /// * `slot` - The index of the slot
+    pub(crate) fn write(&self, dir: &str, slot: usize) -> RLResult {
+        fs::create_dir_all(dir)?;
+        save_file::write_atomic(&slot_path(dir, slot), &serde_yaml::to_string(self)?)?;
+        info!("Saved game state in slot {}", slot + 1);
+        Ok(())
+    }
+
+    /// Returns the saved game upgraded to the current version, its data files are not loaded yet
+    pub(crate) fn game(&self) -> RLResult<GameState> {
+        save_file::upgrade(self.version, self.game.clone(), "slot")
+    }
+}
+
+/// Returns the metadata of every slot, `None` for empty slots and slots which can not be read
+/// # Arguments
+/// * `dir` - The folder of the slots
+pub(crate) fn list_slots(dir: &str) -> Vec<Option<SlotMetadata>> {
+    (0..SAVE_SLOTS)
+        .map(|slot| {
+            let source = fs::read_to_string(slot_path(dir, slot)).ok()?;
+            let header: SlotHeader = serde_yaml::from_str(&source).ok()?;
+            Some(header.metadata)
+        })
+        .collect()
+}
+
+/// Changes the name of the game saved in a slot
+/// # Arguments
+/// * `dir` - The folder of the slots
+/// * `slot` - The index of the slot
+/// * `name` - The new name
+pub(crate) fn rename_slot(dir: &str, slot: usize, name: String) -> RLResult {
+    let mut file = SlotFile::read(dir, slot)?;
+    file.metadata.name = name;
+    file.write(dir, slot)
+}
+
+/// Deletes the game saved in a slot
+/// # Arguments
+/// * `dir` - The folder of the slots
+/// * `slot` - The index of the slot
+pub(crate) fn delete_slot(dir: &str, slot: usize) -> RLResult {
+    info!("Deleting save slot {}", slot + 1);
+    fs::remove_file(slot_path(dir, slot))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::languages::locale::use_english;
+
+    #[test]
+    fn test_save_rename_and_delete_slot() {
+        use_english();
+        // Every test binary uses its own folder, so the slots of the player are not touched
+        let dir = std::env::temp_dir().join(format!("red-life-slots-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let dir = dir.to_string_lossy().to_string();
+        let slot = SAVE_SLOTS - 1;
+        let mut state = GameState::default();
+        state.player.time = 4200;
+        state.player.resources.oxygen = u16::MAX / 2;
+        SlotFile::new(&state, "Crater".to_string())
+            .unwrap()
+            .write(&dir, slot)
+            .unwrap();
+        let metadata = list_slots(&dir)[slot].clone().unwrap();
+        assert_eq!(metadata.name, "Crater");
+        assert_eq!(metadata.play_time, 4200);
+        assert!(metadata.description.starts_with("Air 49%"));
+        rename_slot(&dir, slot, "Valley".to_string()).unwrap();
+        let file = SlotFile::read(&dir, slot).unwrap();
+        assert_eq!(file.metadata.name, "Valley");
+        assert_eq!(file.game().unwrap().player.time, 4200);
+        delete_slot(&dir, slot).unwrap();
+        assert!(list_slots(&dir)[slot].is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_slot_without_description() {
+        let header: SlotHeader = serde_yaml::from_str(
+            "metadata:\n  name: Crater\n  saved_at: 2024-05-01 12:00\n  play_time: 4200\n  milestone: 1\n",
+        )
+        .unwrap();
+        assert_eq!(header.metadata.description, "");
+    }
+}
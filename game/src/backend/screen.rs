@@ -1,8 +1,10 @@
 //! Contains the screen system, which is responsible for managing the different screens of the game.
 //! Author: ["Benedikt Brandmaier", "Marion Hinkel", "Philipp Wolf"]
+use crate::backend::constants::DESIRED_FPS;
+use crate::backend::error::RLError;
+use crate::backend::options::Options;
 use crate::backend::rlcolor::RLColor;
 use crate::backend::utils::{get_draw_params, get_scale};
-use crate::error::RLError;
 use crate::main_menu::mainmenu::MainMenu;
 use crate::{draw, RLResult};
 
@@ -11,7 +13,7 @@ use ggez::graphics::Color;
 use ggez::{event, graphics, Context};
 use std::fmt::Debug;
 use std::sync::mpsc::{channel, Receiver, Sender};
-use std::time::Instant;
+use std::time::Duration;
 use tracing::info;
 
 /// Screens are used to facilitate drawing menus, the game etc. to the screen. They can also send
@@ -33,10 +35,35 @@ pub trait Screen: Debug {
     /// # Arguments
     /// * `sender` - The sender of the screen.
     fn set_sender(&mut self, sender: Sender<StackCommand>);
+    /// Returns the game time of the screen in ticks. Popups expire on the game time of the
+    /// screen, so they stay while the game is paused. Screens without a game time return `None`
+    /// and popups expire on the real time.
+    fn game_time(&self) -> Option<u32> {
+        None
+    }
+    /// Returns whether the screen is drawn over the screen below it instead of replacing it.
+    /// The screen below is still drawn, but only the top screen is updated.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+    /// Called when the window loses the focus while the screen is on top of the stack.
+    /// # Returns
+    /// `RLResult` - Returns an `RlResult`.
+    fn focus_lost(&mut self) -> RLResult {
+        Ok(())
+    }
+    /// Receives a character typed by the player while the screen is on top of the stack.
+    /// # Arguments
+    /// * `character` - The typed character
+    /// # Returns
+    /// `RLResult` - Returns an `RlResult`.
+    fn text_input(&mut self, _character: char) -> RLResult {
+        Ok(())
+    }
 }
 
 /// A Screenstack contains multiple `Screen`s and `Popup`s, the last one of which is drawn to the screen and
-/// updated.
+/// updated. Overlays are drawn over the screens below them.
 pub struct Screenstack {
     screens: Vec<Box<dyn Screen>>,
     popup: Vec<Popup>,
@@ -45,12 +72,12 @@ pub struct Screenstack {
 }
 
 /// Popups are used to display information sent by the game on screen (toplevel)
-/// A Popup is made up of a color, a text and a expiration.
+/// A Popup is made up of a color, a text and the time it is still shown.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Popup {
     color: Color,
     text: String,
-    expiration: Instant,
+    remaining: Duration,
 }
 impl Popup {
     /// Creates a new `Popup` from the nasa template.
@@ -93,7 +120,7 @@ impl Popup {
     /// # Arguments
     /// * `color` - The color of the `Popup`.
     /// * `text` - The text of the `Popup`.
-    /// * `duration` - The duration of the `Popup`.
+    /// * `duration` - The duration of the `Popup` in seconds of game time.
     /// # Returns
     /// `Popup` - Returns a new `Popup`.
     pub(crate) fn new(color: Color, text: String, duration: u64) -> Self {
@@ -101,9 +128,13 @@ impl Popup {
         Self {
             color,
             text,
-            expiration: Instant::now() + std::time::Duration::from_secs(duration),
+            remaining: Duration::from_secs(duration),
         }
     }
+    /// Returns the text of the `Popup`.
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
 }
 impl Screenstack {
     /// Draws all `Popups` at the top left of the screen with their given text and color
@@ -174,9 +205,20 @@ impl Screenstack {
             StackCommand::Popup(popup) => self.popup.push(popup),
         }
     }
-    /// Removes the expired `Popup`s
-    fn remove_popups(&mut self) {
-        self.popup.retain(|popup| popup.expiration > Instant::now());
+    /// Pushes a screen on top of the stack
+    /// # Arguments
+    /// * `screen` - The screen to push
+    pub fn push(&mut self, screen: Box<dyn Screen>) {
+        self.process_command(StackCommand::Push(screen));
+    }
+    /// Shortens the remaining time of the `Popup`s and removes the expired ones
+    /// # Arguments
+    /// * `elapsed` - The time which has passed since the last update
+    fn remove_popups(&mut self, elapsed: Duration) {
+        for popup in &mut self.popup {
+            popup.remaining = popup.remaining.saturating_sub(elapsed);
+        }
+        self.popup.retain(|popup| !popup.remaining.is_zero());
     }
 }
 
@@ -198,26 +240,36 @@ impl event::EventHandler<RLError> for Screenstack {
     /// # Returns
     /// `RLResult` - Returns an `RlResult`
     fn update(&mut self, ctx: &mut Context) -> RLResult {
-        self.remove_popups();
-        self.screens
-            .last_mut()
-            .expect("Failed to get a screen")
-            .update(ctx)?;
+        let screen = self.screens.last_mut().expect("Failed to get a screen");
+        let start = screen.game_time();
+        screen.update(ctx)?;
+        let elapsed = match (start, screen.game_time()) {
+            (Some(start), Some(end)) => {
+                Duration::from_secs(1) / DESIRED_FPS * end.saturating_sub(start)
+            }
+            _ => ctx.time.delta(),
+        };
+        self.remove_popups(elapsed);
         if let Ok(message) = self.receiver.try_recv() {
             self.process_command(message);
         }
         Ok(())
     }
-    /// Redirect the draw command to the last screen.
+    /// Redirect the draw command to the last screen. If it is an overlay, the screens below it
+    /// are drawn first, starting with the last screen which is no overlay.
     /// # Arguments
     /// * `ctx` - The ggez game context
     /// # Returns
     /// `RLResult` - Returns an `RlResult`
     fn draw(&mut self, ctx: &mut Context) -> RLResult {
-        self.screens
-            .last()
-            .expect("Failed to get a screen")
-            .draw(ctx)?;
+        let first = self
+            .screens
+            .iter()
+            .rposition(|screen| !screen.is_overlay())
+            .unwrap_or_default();
+        for screen in &self.screens[first..] {
+            screen.draw(ctx)?;
+        }
         self.draw_popups(ctx)?;
         Ok(())
     }
@@ -229,17 +281,46 @@ impl event::EventHandler<RLError> for Screenstack {
     fn quit_event(&mut self, _ctx: &mut Context) -> RLResult<bool> {
         Ok(true)
     }
+    /// Redirects a typed character to the last screen.
+    /// # Arguments
+    /// * `ctx` - The ggez game context
+    /// * `character` - The typed character
+    /// # Returns
+    /// `RLResult` - Returns an `RlResult`
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> RLResult {
+        self.screens
+            .last_mut()
+            .expect("Failed to get a screen")
+            .text_input(character)
+    }
+    /// Tells the last screen when the window loses the focus, so a running game can be paused.
+    /// # Arguments
+    /// * `ctx` - The ggez game context
+    /// * `gained` - Whether the window gained or lost the focus
+    /// # Returns
+    /// `RLResult` - Returns an `RlResult`
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) -> RLResult {
+        if !gained {
+            self.screens
+                .last_mut()
+                .expect("Failed to get a screen")
+                .focus_lost()?;
+        }
+        Ok(())
+    }
 }
 
-impl Default for Screenstack {
+impl Screenstack {
     /// Creates a new `Screenstack` with a `MainMenu` screen.
+    /// # Arguments
+    /// * `options` - The command line options, which are handed on to every game
     /// # Returns
     /// `Screenstack` - Returns a new `Screenstack`.
-    fn default() -> Self {
-        info!("Default Screenstack created");
+    pub fn new(options: Options) -> Self {
+        info!("Screenstack created");
         let (sender, receiver) = channel();
         Self {
-            screens: vec![Box::new(MainMenu::new(sender.clone()))],
+            screens: vec![Box::new(MainMenu::new(sender.clone(), options))],
             popup: vec![],
             receiver,
             sender,
@@ -247,6 +328,15 @@ impl Default for Screenstack {
     }
 }
 
+impl Default for Screenstack {
+    /// Creates a new `Screenstack` with a `MainMenu` screen and the default options.
+    /// # Returns
+    /// `Screenstack` - Returns a new `Screenstack`.
+    fn default() -> Self {
+        Self::new(Options::default())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -256,4 +346,20 @@ mod test {
         let screenstack = Screenstack::default();
         assert_eq!(1, screenstack.screens.len());
     }
+
+    #[test]
+    fn test_popups_expire() {
+        let mut screenstack = Screenstack::default();
+        screenstack.process_command(StackCommand::Popup(Popup::info("first".to_string())));
+        screenstack.process_command(StackCommand::Popup(Popup::new(
+            RLColor::BLACK,
+            "second".to_string(),
+            20,
+        )));
+        screenstack.remove_popups(Duration::ZERO);
+        assert_eq!(screenstack.popup.len(), 2);
+        screenstack.remove_popups(Duration::from_secs(10));
+        assert_eq!(screenstack.popup.len(), 1);
+        assert_eq!(screenstack.popup[0].text(), "second");
+    }
 }
@@ -0,0 +1,81 @@
+//! Contains the settings of the game, which are kept across all saves.
+use crate::backend::constants::{AUTOSAVE_INTERVAL, AUTOSAVE_INTERVALS, LANGUAGES, SETTINGS_PATH};
+use crate::languages::locale;
+use crate::RLResult;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tracing::{info, warn};
+
+/// The settings chosen by the player
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// The code of the language used for all texts
+    pub language: String,
+    #[serde(default = "default_autosave_interval")]
+    /// The in-game minutes between two periodic autosaves, 0 turns them off
+    pub(crate) autosave_interval: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: LANGUAGES[0].to_string(),
+            autosave_interval: AUTOSAVE_INTERVAL,
+        }
+    }
+}
+
+/// Returns the autosave interval of settings files which do not contain one
+fn default_autosave_interval() -> u32 {
+    AUTOSAVE_INTERVAL
+}
+
+impl Settings {
+    /// Loads the settings file or returns the default settings if it does not exist or is invalid.
+    pub fn load() -> Self {
+        let Ok(source) = fs::read_to_string(SETTINGS_PATH) else {
+            info!("No settings found, using the default settings");
+            return Self::default();
+        };
+        serde_yaml::from_str(&source).unwrap_or_else(|e| {
+            warn!("Invalid settings, using the default settings: {}", e);
+            Self::default()
+        })
+    }
+
+    /// Writes the settings to the settings file.
+    /// # Returns
+    /// * `RLResult` - Fails if the settings file could not be written
+    pub(crate) fn save(&self) -> RLResult {
+        fs::write(SETTINGS_PATH, serde_yaml::to_string(self)?)?;
+        info!("Saved settings");
+        Ok(())
+    }
+
+    /// Switches to the next language in `LANGUAGES` and stores it in the settings
+    /// # Returns
+    /// * `RLResult` - Fails if the next language could not be loaded or the settings could not be saved
+    pub(crate) fn switch_language() -> RLResult {
+        let mut settings = Self::load();
+        let current = LANGUAGES
+            .iter()
+            .position(|language| *language == settings.language)
+            .unwrap_or_default();
+        settings.language = LANGUAGES[(current + 1) % LANGUAGES.len()].to_string();
+        locale::set_language(&settings.language)?;
+        settings.save()
+    }
+
+    /// Switches to the next autosave interval in `AUTOSAVE_INTERVALS` and stores it in the settings
+    /// # Returns
+    /// * `RLResult` - Fails if the settings could not be saved
+    pub(crate) fn switch_autosave_interval() -> RLResult {
+        let mut settings = Self::load();
+        let next = AUTOSAVE_INTERVALS
+            .iter()
+            .position(|interval| *interval == settings.autosave_interval)
+            .map_or(0, |current| current + 1);
+        settings.autosave_interval = AUTOSAVE_INTERVALS[next % AUTOSAVE_INTERVALS.len()];
+        settings.save()
+    }
+}
@@ -0,0 +1,174 @@
+//! Contains the headless `Simulation`, which runs the game without a window or a GPU.
+use crate::backend::game_data::GameData;
+use crate::backend::gamestate::{GameState, Outcome};
+use crate::backend::movement::Input;
+use crate::backend::screen::{Screen, StackCommand};
+use crate::bots::controller::{Controller, Observation};
+use crate::RLResult;
+use std::sync::mpsc::{channel, Receiver};
+use tracing::info;
+
+/// Runs a game with the same rules as the `GameState` screen, but without a window, a GPU or saving.
+/// Popups are collected as notifications instead of being drawn.
+#[derive(Debug)]
+pub struct Simulation {
+    state: GameState,
+    /// Receives the commands the game sends to the `Screenstack`
+    screen_receiver: Receiver<StackCommand>,
+    /// The texts of all popups the game has sent
+    notifications: Vec<String>,
+}
+
+impl Simulation {
+    /// Creates a simulation of a new game
+    /// # Arguments
+    /// * `data` - The data files the game is played with
+    /// * `seed` - The seed of the random number generator, the same seed and inputs always lead to the same game
+    /// # Returns
+    /// * `RLResult<Simulation>` - The simulation or an error if the level is invalid
+    pub fn new(data: GameData, seed: u64) -> RLResult<Self> {
+        Ok(Self::from_state(GameState::new_game(data, seed)?))
+    }
+
+    /// Creates a simulation which continues the given game
+    /// # Arguments
+    /// * `state` - The game state, its data files need to be set already
+    pub(crate) fn from_state(mut state: GameState) -> Self {
+        info!("Creating new simulation");
+        let (screen_sender, screen_receiver) = channel();
+        state.disable_saves = true;
+        state.set_sender(screen_sender);
+        Self {
+            state,
+            screen_receiver,
+            notifications: vec![],
+        }
+    }
+
+    /// Advances the game by one tick, nothing happens anymore once the game is over
+    /// # Arguments
+    /// * `input` - The input of the player in this tick
+    /// # Returns
+    /// * `RLResult<Option<Outcome>>` - The outcome if the game is over
+    pub(crate) fn step(&mut self, input: Input) -> RLResult<Option<Outcome>> {
+        if self.outcome().is_none() {
+            self.state.step(input)?;
+            for command in self.screen_receiver.try_iter() {
+                if let StackCommand::Popup(popup) = command {
+                    self.notifications.push(popup.text().to_string());
+                }
+            }
+        }
+        Ok(self.outcome())
+    }
+
+    /// Lets a `Controller` play the game until it is over
+    /// # Arguments
+    /// * `controller` - Decides the input of every tick
+    /// * `max_ticks` - The game is stopped after this many ticks
+    /// # Returns
+    /// * `RLResult<Option<Outcome>>` - The outcome or `None` if the game was stopped
+    pub fn run(
+        &mut self,
+        controller: &mut dyn Controller,
+        max_ticks: u32,
+    ) -> RLResult<Option<Outcome>> {
+        for _ in 0..max_ticks {
+            if self.outcome().is_some() {
+                break;
+            }
+            let input = controller.act(&Observation::new(&self.state));
+            self.step(input)?;
+        }
+        Ok(self.outcome())
+    }
+
+    /// Returns the outcome of the game or `None` if it is still running
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.state.outcome()
+    }
+
+    /// Returns the current game state
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Returns the texts of all popups the game has sent
+    pub fn notifications(&self) -> &[String] {
+        &self.notifications
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::constants::DESIRED_FPS;
+    use crate::machines::machine::State;
+
+    #[test]
+    fn test_player_dies_without_repairs() {
+        let mut simulation = Simulation::new(GameData::bundled(), 0).unwrap();
+        let mut outcome = None;
+        for _ in 0..60 * 60 * DESIRED_FPS {
+            outcome = simulation.step(Input::default()).unwrap();
+            if outcome.is_some() {
+                break;
+            }
+        }
+        assert_eq!(outcome, Some(Outcome::Died));
+        assert!(simulation.state().player.time > 0);
+    }
+
+    #[test]
+    fn test_walk_to_machine_and_repair() {
+        let mut simulation = Simulation::new(GameData::bundled(), 0).unwrap();
+        let up = Input {
+            up: true,
+            ..Input::default()
+        };
+        let left = Input {
+            left: true,
+            ..Input::default()
+        };
+        // Walk into the top left corner to the oxygen generator
+        for _ in 0..30 {
+            simulation.step(up).unwrap();
+            simulation.step(left).unwrap();
+        }
+        let generator = |simulation: &Simulation| {
+            simulation
+                .state()
+                .machines
+                .iter()
+                .find(|machine| machine.name == "Sauerstoffgenerator")
+                .unwrap()
+                .state
+                .clone()
+        };
+        assert_eq!(generator(&simulation), State::Broken);
+        simulation
+            .step(Input {
+                interact: true,
+                ..Input::default()
+            })
+            .unwrap();
+        // The player has no super glue yet, so the repair is refused
+        assert_eq!(generator(&simulation), State::Broken);
+        assert!(!simulation.notifications().is_empty());
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let mut first = Simulation::new(GameData::bundled(), 3).unwrap();
+        let mut second = Simulation::new(GameData::bundled(), 3).unwrap();
+        for _ in 0..10 * 60 * DESIRED_FPS {
+            first.step(Input::default()).unwrap();
+            second.step(Input::default()).unwrap();
+        }
+        assert_eq!(
+            serde_yaml::to_string(first.state()).unwrap(),
+            serde_yaml::to_string(second.state()).unwrap()
+        );
+        assert_eq!(first.notifications(), second.notifications());
+    }
+}
@@ -1,13 +1,13 @@
 //! Miscellaneous utilities used by the backend.
 //! Author: ["Benedikt Brandmaier", "Marion Hinkel"]
-use crate::backend::constants::{PLAYER_ICON_SIZE, SCREEN_RESOLUTION};
+use crate::backend::constants::{DESIRED_FPS, PLAYER_ICON_SIZE, SCREEN_RESOLUTION};
 use ggez::glam::Vec2;
 use ggez::graphics::{Color, Rect};
 use ggez::Context;
 
 /// This returns the scale so we can have resolution-agnostic scaling
 /// Use it in your drawing calls like this:
-/// ```rust
+/// ```ignore
 /// let scale = get_scale(ctx);
 /// graphics::draw(ctx, &self.img, graphics::DrawParam::default().scale(scale))?;
 /// ```
@@ -33,6 +33,24 @@ pub fn is_colliding(player_pos: (usize, usize), area: &Rect) -> bool {
         && area.y + area.h > player_pos.1 as f32
 }
 
+/// Formats a time in ticks as hours, minutes and seconds
+/// # Arguments
+/// * `ticks` - The time in ticks
+pub fn format_play_time(ticks: u32) -> String {
+    let time = ticks / DESIRED_FPS;
+    format!("{}h {}m {}s", time / 3600, time / 60 % 60, time % 60)
+}
+
+/// Returns the FNV-1a hash of the given data, which unlike the hasher of the standard library
+/// stays the same across Rust versions and can therefore be stored in files
+/// # Arguments
+/// * `data` - The data to hash
+pub(crate) fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 /// This macro is used for simplifying drawing with scaling.
 /// It takes a canvas, a `Drawable`, an (optional) position(as `Vec2` for example) and a scale as `Vec2`.
 /// Author: ["Benedikt Brandmaier"]
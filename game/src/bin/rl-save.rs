@@ -0,0 +1,510 @@
+//! `rl-save` inspects and edits the save files of Red Life, it has to be run next to the assets
+//! folder like the game. It reads saves with the modules of the game, so older saves are upgraded
+//! and damaged saves are detected exactly like in the game.
+//! * `rl-save summary <save>` - Prints the resources, inventory, machines, events and progress
+//! * `rl-save give <save> <item id> <amount>` - Gives the player items, negative amounts take them
+//!   and the items the player can not carry are put on the floor
+//! * `rl-save set-machine <save> <machine> <broken|idle|running>` - Sets the state of a machine
+//! * `rl-save set-resource <save> <oxygen|energy|life> <value>` - Sets a resource of the player
+//! * `rl-save validate <save>` - Checks that the save only refers to existing data
+//! * `rl-save diff <save> <other save>` - Prints every field which differs between two saves
+//!
+//! Edited saves are written in the current save format.
+#![warn(clippy::pedantic)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_possible_truncation)]
+use game::backend::constants::{DEFAULT_LEVEL_PATH, MAX_CARRY_WEIGHT};
+use game::backend::error::{create_data_error, RLError};
+use game::backend::game_data::GameData;
+use game::backend::gamestate::{GameCommand, GameState};
+use game::backend::save_file;
+use game::backend::utils::format_play_time;
+use game::machines::machine::State;
+use game::RLResult;
+use serde_yaml::Value;
+use std::fs;
+use std::sync::mpsc::channel;
+
+/// Describes how to use the tool, printed if the arguments are invalid
+const USAGE: &str = "usage:
+  rl-save summary <save>
+  rl-save give <save> <item id> <amount>
+  rl-save set-machine <save> <machine> <broken|idle|running>
+  rl-save set-resource <save> <oxygen|energy|life> <value>
+  rl-save validate <save>
+  rl-save diff <save> <other save>";
+
+/// Runs the command given on the command line
+fn main() -> RLResult {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["summary", path] => println!("{}", summary(&read_save(path)?)),
+        ["give", path, item, amount] => {
+            let mut state = read_save(path)?;
+            let data = load_data(&state)?;
+            give(&mut state, &data, item, parse(amount)?)?;
+            write_save(path, &state)?;
+        }
+        ["set-machine", path, machine, machine_state] => {
+            let mut state = read_save(path)?;
+            set_machine(&mut state, machine, &parse_state(machine_state)?)?;
+            write_save(path, &state)?;
+        }
+        ["set-resource", path, resource, value] => {
+            let mut state = read_save(path)?;
+            set_resource(&mut state, resource, parse(value)?)?;
+            write_save(path, &state)?;
+        }
+        ["validate", path] => {
+            let state = read_save(path)?;
+            let problems = validate(&state, &load_data(&state)?);
+            for problem in &problems {
+                println!("{problem}");
+            }
+            if !problems.is_empty() {
+                return Err(create_data_error(
+                    path,
+                    "save",
+                    format!("{} problems found", problems.len()),
+                ));
+            }
+            println!("{path} is valid");
+        }
+        ["diff", path, other] => {
+            for difference in diff(&read_save(path)?, &read_save(other)?)? {
+                println!("{difference}");
+            }
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+    Ok(())
+}
+
+/// Parses a number given on the command line
+/// # Arguments
+/// * `value` - The argument
+fn parse<T: std::str::FromStr>(value: &str) -> RLResult<T> {
+    value
+        .parse()
+        .map_err(|_| RLError::InitError(format!("\"{value}\" is not a valid number")))
+}
+
+/// Parses the state of a machine given on the command line
+/// # Arguments
+/// * `value` - The argument
+fn parse_state(value: &str) -> RLResult<State> {
+    match value.to_lowercase().as_str() {
+        "broken" => Ok(State::Broken),
+        "idle" => Ok(State::Idle),
+        "running" => Ok(State::Running),
+        _ => Err(RLError::InitError(format!(
+            "\"{value}\" is no machine state, use broken, idle or running"
+        ))),
+    }
+}
+
+/// Reads a save file like the game does
+/// # Arguments
+/// * `path` - The path of the save file
+fn read_save(path: &str) -> RLResult<GameState> {
+    save_file::deserialize(&fs::read_to_string(path)?, path)
+}
+
+/// Writes a save file in the current save format
+/// # Arguments
+/// * `path` - The path of the save file
+/// * `state` - The saved game
+fn write_save(path: &str, state: &GameState) -> RLResult {
+    save_file::write_atomic(path, &save_file::serialize(state)?)?;
+    println!("Saved {path}");
+    Ok(())
+}
+
+/// Loads the data files the saved game is played with
+/// # Arguments
+/// * `state` - The saved game
+fn load_data(state: &GameState) -> RLResult<GameData> {
+    GameData::load(state.level_path.as_deref().unwrap_or(DEFAULT_LEVEL_PATH))
+}
+
+/// Returns a summary of a saved game
+/// # Arguments
+/// * `state` - The saved game
+fn summary(state: &GameState) -> String {
+    let player = &state.player;
+    let mut lines = vec![
+        format!(
+            "Milestone {}, played {}",
+            player.milestone,
+            format_play_time(player.time)
+        ),
+        format!("Position: {:?}", player.position),
+        "Resources:".to_string(),
+    ];
+    for ((name, value), change) in ["oxygen", "energy", "life"]
+        .iter()
+        .zip(player.resources)
+        .zip(player.resources_change)
+    {
+        lines.push(format!("  {name}: {value} ({change:+} per tick)"));
+    }
+    lines.push("Inventory:".to_string());
+    for (id, amount) in &player.inventory {
+        lines.push(format!("  {id}: {amount}"));
+    }
+    lines.push("Machines:".to_string());
+    for machine in &state.machines {
+        lines.push(format!("  {}: {}", machine.name, machine.state));
+    }
+    lines.push("Active events:".to_string());
+    for event in &state.events {
+        lines.push(format!("  {}", event.get_name()));
+    }
+    lines.push("Objectives:".to_string());
+    for (id, activated_at) in &state.objectives.active {
+        lines.push(format!(
+            "  {id}: active since {}",
+            format_play_time(*activated_at)
+        ));
+    }
+    for id in &state.objectives.completed {
+        lines.push(format!("  {id}: completed"));
+    }
+    lines.join("\n")
+}
+
+/// Gives the player items, a negative amount takes them away.
+/// Like in the game, the items the player can not carry are put on the floor.
+/// # Arguments
+/// * `state` - The saved game
+/// * `data` - The data files of the game, needed to find the item
+/// * `id` - The id of the item
+/// * `amount` - The amount of the item
+fn give(state: &mut GameState, data: &GameData, id: &str, amount: i32) -> RLResult {
+    if data.item_registry.get(id).is_none() {
+        return Err(RLError::InitError(format!("There is no item \"{id}\"")));
+    }
+    state.item_registry = data.item_registry.clone();
+    state.add_items(&[(id.to_string(), amount)]);
+    Ok(())
+}
+
+/// Sets the state of a machine. The resource change of the player is adjusted like in the game,
+/// if the machine starts or stops running.
+/// # Arguments
+/// * `state` - The saved game
+/// * `name` - The name of the machine
+/// * `machine_state` - The new state of the machine
+fn set_machine(state: &mut GameState, name: &str, machine_state: &State) -> RLResult {
+    let machine = state
+        .machines
+        .iter_mut()
+        .find(|machine| machine.name == name)
+        .ok_or_else(|| RLError::InitError(format!("There is no machine \"{name}\"")))?;
+    let (sender, receiver) = channel();
+    let (screen_sender, _screen_receiver) = channel();
+    machine.init(sender, screen_sender);
+    machine.change_state_to(machine_state);
+    for command in receiver.try_iter() {
+        if let GameCommand::ResourceChange(change) = command {
+            state.player.resources_change = state.player.resources_change + change;
+        }
+    }
+    Ok(())
+}
+
+/// Sets a resource of the player
+/// # Arguments
+/// * `state` - The saved game
+/// * `resource` - The name of the resource
+/// * `value` - The new value of the resource
+fn set_resource(state: &mut GameState, resource: &str, value: u16) -> RLResult {
+    let resources = &mut state.player.resources;
+    match resource {
+        "oxygen" => resources.oxygen = value,
+        "energy" => resources.energy = value,
+        "life" => resources.life = value,
+        _ => {
+            return Err(RLError::InitError(format!(
+                "\"{resource}\" is no resource, use oxygen, energy or life"
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Checks the amounts and weight of the inventory and the items lying on the floor
+/// # Arguments
+/// * `state` - The saved game
+/// * `data` - The data files of the game
+/// # Returns
+/// * `Vec<String>` - A description of every problem, empty if the items are valid
+fn validate_items(state: &GameState, data: &GameData) -> Vec<String> {
+    let mut problems = vec![];
+    for (id, amount) in &state.player.inventory {
+        match data.item_registry.get(id) {
+            None => problems.push(format!("player.inventory: unknown item {id}")),
+            Some(item) if !(0..=item.stack_limit).contains(amount) => problems.push(format!(
+                "player.inventory: {amount} {id} is not between 0 and {}",
+                item.stack_limit
+            )),
+            Some(_) => {}
+        }
+    }
+    let weight = data.item_registry.weight(&state.player.inventory);
+    if weight > MAX_CARRY_WEIGHT {
+        problems.push(format!(
+            "player.inventory: weighs {weight}, more than {MAX_CARRY_WEIGHT}"
+        ));
+    }
+    for (i, pile) in state.floor_items.iter().enumerate() {
+        if data.item_registry.get(&pile.id).is_none() {
+            problems.push(format!("floor_items.{i}: unknown item {}", pile.id));
+        }
+        if pile.amount <= 0 {
+            problems.push(format!(
+                "floor_items.{i}: the amount {} is not positive",
+                pile.amount
+            ));
+        }
+    }
+    problems
+}
+
+/// Checks that a saved game only refers to items, machines, events and objectives which exist
+/// # Arguments
+/// * `state` - The saved game
+/// * `data` - The data files of the game
+/// # Returns
+/// * `Vec<String>` - A description of every problem, empty if the save is valid
+fn validate(state: &GameState, data: &GameData) -> Vec<String> {
+    let mut problems = validate_items(state, data);
+    if data.level.is_blocked(state.player.position) {
+        problems.push(format!(
+            "player.position: {:?} is not walkable",
+            state.player.position
+        ));
+    }
+    for machine in &state.machines {
+        if !data
+            .machine_catalog
+            .iter()
+            .any(|known| known.name == machine.name)
+        {
+            problems.push(format!("machines: unknown machine {}", machine.name));
+        }
+        for trade in &machine.trades {
+            for (id, _) in &trade.cost {
+                if data.item_registry.get(id).is_none() {
+                    problems.push(format!(
+                        "machines.{}.{}: unknown item {id}",
+                        machine.name, trade.name
+                    ));
+                }
+            }
+            if trade.refund > 100 {
+                problems.push(format!(
+                    "machines.{}.{}: the refund {} is more than 100 percent",
+                    machine.name, trade.name, trade.refund
+                ));
+            }
+        }
+        for recipe in &machine.recipes {
+            for (id, _) in recipe.inputs.iter().chain(&recipe.outputs) {
+                if data.item_registry.get(id).is_none() {
+                    problems.push(format!(
+                        "machines.{}.{}: unknown item {id}",
+                        machine.name, recipe.name
+                    ));
+                }
+            }
+        }
+        for (id, _) in &machine.output {
+            if data.item_registry.get(id).is_none() {
+                problems.push(format!(
+                    "machines.{}.output: unknown item {id}",
+                    machine.name
+                ));
+            }
+        }
+        for (name, _) in &machine.queue {
+            if !machine.recipes.iter().any(|recipe| recipe.name == *name) {
+                problems.push(format!(
+                    "machines.{}.queue: unknown recipe {name}",
+                    machine.name
+                ));
+            }
+        }
+    }
+    for event in &state.events {
+        let name = event.get_name();
+        if !data
+            .event_catalog
+            .events
+            .iter()
+            .any(|known| known.name == name)
+        {
+            problems.push(format!("events: unknown event {name}"));
+        }
+    }
+    let objectives = &state.objectives;
+    for id in objectives
+        .active
+        .iter()
+        .map(|(id, _)| id)
+        .chain(&objectives.completed)
+    {
+        if data.objective_catalog.get(id).is_none() {
+            problems.push(format!("objectives: unknown objective {id}"));
+        }
+    }
+    problems
+}
+
+/// Returns every field which differs between two saved games
+/// # Arguments
+/// * `state` - The first saved game
+/// * `other` - The second saved game
+/// # Returns
+/// * `RLResult<Vec<String>>` - The path of every differing field with both values
+fn diff(state: &GameState, other: &GameState) -> RLResult<Vec<String>> {
+    let mut differences = vec![];
+    diff_values(
+        "",
+        &serde_yaml::to_value(state)?,
+        &serde_yaml::to_value(other)?,
+        &mut differences,
+    );
+    Ok(differences)
+}
+
+/// Compares two values of a save recursively
+/// # Arguments
+/// * `path` - The path of the values inside the save
+/// * `value` - The value of the first save
+/// * `other` - The value of the second save
+/// * `differences` - The differences found so far
+fn diff_values(path: &str, value: &Value, other: &Value, differences: &mut Vec<String>) {
+    let child_path = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (value, other) {
+        (Value::Mapping(mapping), Value::Mapping(other_mapping)) => {
+            for (key, child) in mapping {
+                let other_child = other_mapping.get(key).unwrap_or(&Value::Null);
+                diff_values(&child_path(&show(key)), child, other_child, differences);
+            }
+            for (key, other_child) in other_mapping {
+                if !mapping.contains_key(key) {
+                    diff_values(
+                        &child_path(&show(key)),
+                        &Value::Null,
+                        other_child,
+                        differences,
+                    );
+                }
+            }
+        }
+        (Value::Sequence(sequence), Value::Sequence(other_sequence)) => {
+            for index in 0..sequence.len().max(other_sequence.len()) {
+                diff_values(
+                    &child_path(&index.to_string()),
+                    sequence.get(index).unwrap_or(&Value::Null),
+                    other_sequence.get(index).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        _ if value != other => {
+            differences.push(format!("{path}: {} -> {}", show(value), show(other)));
+        }
+        _ => {}
+    }
+}
+
+/// Shows a value of a save in a single line
+/// # Arguments
+/// * `value` - The value
+fn show(value: &Value) -> String {
+    match value {
+        Value::Null => "~".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => value.clone(),
+        Value::Sequence(sequence) => {
+            format!(
+                "[{}]",
+                sequence.iter().map(show).collect::<Vec<_>>().join(", ")
+            )
+        }
+        Value::Mapping(mapping) => format!(
+            "{{{}}}",
+            mapping
+                .iter()
+                .map(|(key, value)| format!("{}: {}", show(key), show(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Tagged(tagged) => format!("{} {}", tagged.tag, show(&tagged.value)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixture() -> GameState {
+        save_file::deserialize(include_str!("../../test-saves/v1.yaml"), "v1.yaml").unwrap()
+    }
+
+    #[test]
+    fn test_edit_save() {
+        let mut state = fixture();
+        give(&mut state, &GameData::bundled(), "super_glue", 3).unwrap();
+        assert_eq!(state.player.get_item_amount("super_glue"), 5);
+        assert!(give(&mut state, &GameData::bundled(), "gold", 1).is_err());
+        // The items the player can not carry are put on the floor
+        give(&mut state, &GameData::bundled(), "benzin", 10).unwrap();
+        assert_eq!(state.player.get_item_amount("benzin"), 5);
+        assert_eq!(state.floor_items[0].amount, 8);
+        let before = state.player.resources_change;
+        set_machine(&mut state, "Sauerstoffgenerator", &State::Running).unwrap();
+        assert_eq!(state.machines[0].state, State::Running);
+        assert_ne!(state.player.resources_change, before);
+        set_machine(&mut state, "Sauerstoffgenerator", &State::Idle).unwrap();
+        assert_eq!(state.player.resources_change, before);
+    }
+
+    #[test]
+    fn test_validate_save() {
+        let mut state = fixture();
+        assert_eq!(validate(&state, &GameData::bundled()), Vec::<String>::new());
+        state.player.inventory.push(("gold".to_string(), 1));
+        state.objectives.completed.push("escape".to_string());
+        assert_eq!(validate(&state, &GameData::bundled()).len(), 2);
+    }
+
+    #[test]
+    fn test_diff_saves() {
+        let state = fixture();
+        let mut other = fixture();
+        assert!(diff(&state, &other).unwrap().is_empty());
+        set_resource(&mut other, "life", 100).unwrap();
+        other.player.inventory.pop();
+        assert_eq!(
+            diff(&state, &other).unwrap(),
+            vec![
+                "player.inventory.2: [printed_part, 1] -> ~".to_string(),
+                "player.resources.life: 65535 -> 100".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Contains the `Controller` trait, which lets bots play the game instead of the keyboard.
+use crate::backend::gamestate::GameState;
+use crate::backend::movement::Input;
+use crate::game_core::event::Event;
+use crate::game_core::resources::Resources;
+use crate::machines::machine::State;
+use ggez::graphics::Rect;
+use std::fmt::Debug;
+
+/// Plays the game by deciding the input of every tick.
+/// The input is handled exactly like the input read from the keyboard.
+pub trait Controller: Debug {
+    /// Decides the input of the next tick
+    /// # Arguments
+    /// * `observation` - What the player can see of the game before the tick
+    /// # Returns
+    /// * `Input` - The keys pressed in this tick
+    fn act(&mut self, observation: &Observation) -> Input;
+}
+
+/// What the player can see of the game in a tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// The current time in ticks
+    pub(crate) time: u32,
+    pub(crate) position: (usize, usize),
+    pub(crate) resources: Resources<u16>,
+    /// The change of the resources per tick
+    pub(crate) resources_change: Resources<i16>,
+    /// Item ids and the amount the player owns
+    pub(crate) inventory: Vec<(String, i32)>,
+    pub(crate) machines: Vec<MachineObservation>,
+    /// The index of the machine the player can interact with, if there is one
+    pub(crate) interactable: Option<usize>,
+    /// Whether the interaction menu of a machine is open
+    pub(crate) menu_open: bool,
+    /// The names of the active events
+    pub(crate) events: Vec<String>,
+    /// The ids of the active objectives
+    pub(crate) objectives: Vec<String>,
+}
+
+/// What the player can see of a machine
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MachineObservation {
+    pub(crate) name: String,
+    pub(crate) state: State,
+    /// The area the player can not walk into
+    pub(crate) hitbox: Rect,
+    /// Whether the timer of a trade is running
+    pub(crate) busy: bool,
+    /// The amount of items waiting in the output buffer
+    pub(crate) output: i32,
+}
+
+impl Observation {
+    /// Observes the game
+    /// # Arguments
+    /// * `state` - The game state to observe
+    pub(crate) fn new(state: &GameState) -> Self {
+        Self {
+            time: state.player.time,
+            position: state.player.position,
+            resources: state.player.resources,
+            resources_change: state.player.resources_change,
+            inventory: state.player.inventory.clone(),
+            machines: state
+                .machines
+                .iter()
+                .map(|machine| MachineObservation {
+                    name: machine.name.clone(),
+                    state: machine.state.clone(),
+                    hitbox: machine.hitbox,
+                    busy: machine.is_busy(),
+                    output: machine.buffered_units(),
+                })
+                .collect(),
+            interactable: state
+                .machines
+                .iter()
+                .position(|machine| machine.is_interactable(state.player.position)),
+            menu_open: state.interaction_menu.is_some(),
+            events: state.events.iter().map(Event::get_name).collect(),
+            objectives: state
+                .objectives
+                .active
+                .iter()
+                .map(|(id, _)| id.clone())
+                .collect(),
+        }
+    }
+
+    /// Returns the amount of an item the player owns
+    /// # Arguments
+    /// * `id` - The id of the item
+    pub(crate) fn item_amount(&self, id: &str) -> i32 {
+        self.inventory
+            .iter()
+            .find(|(item, _)| item == id)
+            .map_or(0, |(_, amount)| *amount)
+    }
+}
@@ -0,0 +1,2 @@
+pub(crate) mod controller;
+pub mod scripted_agent;
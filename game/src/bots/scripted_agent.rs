@@ -0,0 +1,213 @@
+//! Contains the `ScriptedAgent`, a bot which plays through the campaign with fixed rules.
+use crate::backend::constants::{MOVEMENT_SPEED, PLAYER_ICON_SIZE};
+use crate::backend::movement::Input;
+use crate::bots::controller::{Controller, MachineObservation, Observation};
+use crate::machines::machine::State;
+
+/// The y position of a corridor through the middle of the habitat, which is free of machines
+const CORRIDOR_Y: usize = 500;
+/// The ticks the agent waits after an interaction, so the game can process the trade
+const INTERACTION_COOLDOWN: u32 = 10;
+
+/// Plays through the campaign by walking to the machines and trading with them in a fixed order
+/// of priorities. It keeps the oxygen and energy up and then produces super glue and printed parts,
+/// which it collects from the machines, until it can repair the communication module and send the
+/// emergency signal.
+/// It is used to check that the campaign can still be won after balance changes.
+#[derive(Debug, Default)]
+pub struct ScriptedAgent {
+    /// The agent does not interact before this tick
+    next_interaction: u32,
+}
+
+/// Returns the share of the maximum of a resource
+fn share(resource: u16) -> f32 {
+    f32::from(resource) / f32::from(u16::MAX)
+}
+
+/// Returns the distance between two coordinates
+fn distance(a: usize, b: usize) -> usize {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Returns where the player stands to interact with a machine. The player walks through the
+/// corridor and stands on the side of the machine which faces it.
+/// # Arguments
+/// * `machine` - The machine to interact with
+#[allow(clippy::cast_sign_loss)]
+fn standing_position(machine: &MachineObservation) -> (usize, usize) {
+    let (width, height) = (PLAYER_ICON_SIZE.0 as f32, PLAYER_ICON_SIZE.1 as f32);
+    let hitbox = machine.hitbox;
+    let corridor = CORRIDOR_Y as f32;
+    let (x, y) = if hitbox.y < corridor + height && hitbox.y + hitbox.h > corridor {
+        // The machine reaches into the corridor, so the player stands next to it
+        (hitbox.x - width - 5., corridor)
+    } else if hitbox.y < corridor {
+        (hitbox.x + (hitbox.w - width) / 2., hitbox.y + hitbox.h + 5.)
+    } else {
+        (hitbox.x + (hitbox.w - width) / 2., hitbox.y - height - 5.)
+    };
+    (x.max(0.) as usize, y.max(0.) as usize)
+}
+
+impl ScriptedAgent {
+    /// Decides which machine to trade with next
+    /// # Arguments
+    /// * `observation` - What the agent can see of the game
+    /// # Returns
+    /// * `Option<(usize, bool)>` - The index of the machine and whether to trade with it now or to
+    /// wait next to it, `None` if the level is missing a machine of the campaign
+    fn goal(observation: &Observation) -> Option<(usize, bool)> {
+        let machines = &observation.machines;
+        let find = |name: &str| machines.iter().position(|machine| machine.name == name);
+        let ready =
+            |index: &usize, state: State| machines[*index].state == state && !machines[*index].busy;
+        let glue = observation.item_amount("super_glue");
+        let parts = observation.item_amount("printed_part");
+        let benzin = observation.item_amount("benzin");
+        let oxygen = share(observation.resources.oxygen);
+        let energy = share(observation.resources.energy);
+        let objective_active = |id: &str| observation.objectives.iter().any(|active| active == id);
+
+        let oxygen_generator = find("Sauerstoffgenerator")?;
+        let power_generator = find("Stromgenerator")?;
+        let workshop = find("Werkermaschine")?;
+        let printer = find("3D-Drucker")?;
+        let communication = find("Kommunikationsmodul")?;
+        let leak = machines.iter().position(|machine| {
+            machine.name == "Loch" && machine.state == State::Running && !machine.busy
+        });
+
+        let oxygen_repaired =
+            machines[oxygen_generator].state != State::Broken && !machines[oxygen_generator].busy;
+        let power_ready = (ready(&power_generator, State::Broken) && benzin > 0)
+            || ready(&power_generator, State::Idle);
+        let needs_power = energy < 0.4
+            || (objective_active("life_support") && oxygen_repaired)
+            || (ready(&communication, State::Idle) && energy < 0.6);
+        let needed_parts = if machines[communication].state == State::Broken {
+            3
+        } else {
+            1
+        };
+
+        let start_oxygen = ready(&oxygen_generator, State::Idle) && oxygen < 0.4 && energy > 0.1;
+        let stop_oxygen =
+            ready(&oxygen_generator, State::Running) && (oxygen > 0.9 || energy < 0.1);
+        let send_signal = ready(&communication, State::Idle)
+            && objective_active("rescue")
+            && glue >= 1
+            && parts >= 1
+            && energy >= 0.6;
+
+        let goal = if start_oxygen || stop_oxygen {
+            oxygen_generator
+        } else if power_ready && needs_power {
+            power_generator
+        } else if let (Some(leak), true) = (leak, glue >= 2) {
+            leak
+        } else if let Some(output) = machines.iter().position(|machine| machine.output > 0) {
+            // Walking to the machine collects its output
+            return Some((output, false));
+        } else if ready(&workshop, State::Broken) && parts >= 1 {
+            workshop
+        } else if ready(&oxygen_generator, State::Broken) && glue >= 2 {
+            oxygen_generator
+        } else if ready(&printer, State::Broken) && glue >= 2 {
+            printer
+        } else if (ready(&communication, State::Broken) && glue >= 5 && parts >= 3) || send_signal {
+            communication
+        } else if ready(&printer, State::Idle) && parts < needed_parts && glue >= 2 {
+            printer
+        } else {
+            // Produce super glue or wait for the workshop to finish
+            return Some((workshop, ready(&workshop, State::Idle)));
+        };
+        Some((goal, true))
+    }
+
+    /// Returns the input which moves the player towards a position.
+    /// The player walks to the corridor first, then along it and then to the position.
+    /// # Arguments
+    /// * `from` - The position of the player
+    /// * `to` - The position the player walks to
+    fn walk(from: (usize, usize), to: (usize, usize)) -> Input {
+        let mut input = Input::default();
+        if distance(from.0, to.0) >= MOVEMENT_SPEED {
+            if distance(from.1, CORRIDOR_Y) >= MOVEMENT_SPEED {
+                input.up = from.1 > CORRIDOR_Y;
+                input.down = from.1 < CORRIDOR_Y;
+            } else {
+                input.left = from.0 > to.0;
+                input.right = from.0 < to.0;
+            }
+        } else if distance(from.1, to.1) >= MOVEMENT_SPEED {
+            input.up = from.1 > to.1;
+            input.down = from.1 < to.1;
+        }
+        input
+    }
+}
+
+impl Controller for ScriptedAgent {
+    fn act(&mut self, observation: &Observation) -> Input {
+        if observation.menu_open {
+            // The agent only trades with machines whose first option is the one it wants
+            return Input {
+                choose: Some(0),
+                ..Input::default()
+            };
+        }
+        let Some((goal, trade)) = Self::goal(observation) else {
+            return Input::default();
+        };
+        if observation.interactable != Some(goal) {
+            return Self::walk(
+                observation.position,
+                standing_position(&observation.machines[goal]),
+            );
+        }
+        if trade && observation.time >= self.next_interaction {
+            self.next_interaction = observation.time + INTERACTION_COOLDOWN;
+            return Input {
+                interact: true,
+                ..Input::default()
+            };
+        }
+        Input::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::constants::BOT_MAX_TICKS;
+    use crate::backend::game_data::GameData;
+    use crate::backend::gamestate::Outcome;
+    use crate::backend::simulation::Simulation;
+
+    #[test]
+    fn test_agent_wins_campaign() {
+        for seed in [0, 7, 21] {
+            let mut simulation = Simulation::new(GameData::bundled(), seed).unwrap();
+            let outcome = simulation
+                .run(&mut ScriptedAgent::default(), BOT_MAX_TICKS)
+                .unwrap();
+            assert_eq!(outcome, Some(Outcome::Won), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_walk_through_corridor() {
+        let input = ScriptedAgent::walk((100, 200), (800, 300));
+        assert!(input.down && !input.right);
+        let input = ScriptedAgent::walk((100, CORRIDOR_Y), (800, 300));
+        assert!(input.right && !input.down);
+        let input = ScriptedAgent::walk((800, CORRIDOR_Y), (800, 300));
+        assert!(input.up);
+    }
+}
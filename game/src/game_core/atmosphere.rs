@@ -0,0 +1,299 @@
+//! Contains the atmosphere of the habitat, which keeps the pressure and the oxygen of every room.
+use crate::backend::constants::{DOOR_FLOW, PLAYER_INTERACTION_RADIUS};
+use crate::backend::gamestate::GameState;
+use crate::backend::level::Level;
+use crate::backend::rlcolor::RLColor;
+use crate::backend::utils::{get_draw_params, is_colliding};
+use crate::machines::machine::{Machine, State};
+use crate::{draw, tr, RLResult};
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, DrawMode, Mesh, Rect, Text, TextFragment};
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// The air in one room of the habitat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RoomAir {
+    /// The amount of air in the room, holes let it escape
+    pub(crate) pressure: u16,
+    /// The amount of oxygen in the air of the room, it is never more than the pressure
+    pub(crate) oxygen: u16,
+}
+
+/// The atmosphere of the habitat. Every room of the level has its own air: the machines standing
+/// in a room add or use up its oxygen, holes let its air escape and the player breathes the air of
+/// the room they stand in. Open doors let the air flow between the rooms they connect.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Atmosphere {
+    /// The air of every room, in the order of the rooms of the level
+    pub(crate) rooms: Vec<RoomAir>,
+    /// Whether each door of the level is open
+    pub(crate) doors: Vec<bool>,
+}
+
+impl Atmosphere {
+    /// Creates the atmosphere of a level with pressurized rooms
+    /// # Arguments
+    /// * `level` - The level whose rooms and doors are used
+    /// * `oxygen` - The oxygen every room starts with
+    pub(crate) fn new(level: &Level, oxygen: u16) -> Self {
+        Self {
+            rooms: vec![
+                RoomAir {
+                    pressure: u16::MAX,
+                    oxygen,
+                };
+                level.rooms.len()
+            ],
+            doors: level.doors.iter().map(|door| door.open).collect(),
+        }
+    }
+
+    /// Returns whether the atmosphere describes the rooms and doors of a level, saves of older
+    /// versions and of other levels do not
+    /// # Arguments
+    /// * `level` - The level to compare with
+    pub(crate) fn fits(&self, level: &Level) -> bool {
+        self.rooms.len() == level.rooms.len() && self.doors.len() == level.doors.len()
+    }
+
+    /// Advances the atmosphere by one tick
+    /// # Arguments
+    /// * `level` - The level with the rooms and doors of the atmosphere
+    /// * `machines` - The machines of the game, running machines change the air of their room
+    /// * `player_room` - The room the player stands in
+    /// * `breathing` - The oxygen per tick the player changes in their room, mostly by breathing
+    pub(crate) fn tick(
+        &mut self,
+        level: &Level,
+        machines: &[Machine],
+        player_room: usize,
+        breathing: i16,
+    ) {
+        let mut changes = vec![(0_i32, 0_i32); self.rooms.len()];
+        changes[player_room].0 += i32::from(breathing);
+        for machine in machines.iter().filter(|m| m.state == State::Running) {
+            let Some(room) = level.room_at(machine.center()) else {
+                continue;
+            };
+            if machine.name == "Loch" {
+                // Holes let the air escape instead of using up its oxygen
+                changes[room].1 -= i32::from(machine.get_oxygen());
+            } else {
+                changes[room].0 += i32::from(machine.get_oxygen());
+            }
+        }
+        for (air, (oxygen, leak)) in self.rooms.iter_mut().zip(changes) {
+            let mut pressure = i32::from(air.pressure);
+            let mut room_oxygen = i32::from(air.oxygen);
+            if leak > 0 {
+                // The escaping air takes its share of oxygen with it
+                room_oxygen -= (room_oxygen * leak).checked_div(pressure).unwrap_or(0);
+                pressure -= leak;
+            }
+            if oxygen > 0 {
+                pressure += oxygen;
+            }
+            room_oxygen += oxygen;
+            air.pressure = clamp(pressure, i32::from(u16::MAX));
+            air.oxygen = clamp(room_oxygen, i32::from(air.pressure));
+        }
+        for (door, open) in level.doors.iter().zip(&self.doors) {
+            if !open {
+                continue;
+            }
+            let (first, second) = door.connects;
+            let (a, b) = (self.rooms[first], self.rooms[second]);
+            let pressure = (i32::from(a.pressure) - i32::from(b.pressure)) / DOOR_FLOW;
+            let oxygen = (i32::from(a.oxygen) - i32::from(b.oxygen)) / DOOR_FLOW;
+            for (room, sign) in [(first, -1), (second, 1)] {
+                let air = &mut self.rooms[room];
+                air.pressure = clamp(
+                    i32::from(air.pressure) + sign * pressure,
+                    i32::from(u16::MAX),
+                );
+                air.oxygen = clamp(
+                    i32::from(air.oxygen) + sign * oxygen,
+                    i32::from(air.pressure),
+                );
+            }
+        }
+    }
+}
+
+/// Limits a value to the range from 0 to `max`
+/// # Arguments
+/// * `value` - The value to limit
+/// * `max` - The largest allowed value, at most `u16::MAX`
+fn clamp(value: i32, max: i32) -> u16 {
+    u16::try_from(value.clamp(0, max)).unwrap_or(u16::MAX)
+}
+
+impl GameState {
+    /// Returns the room the player stands in, the first room if they are outside of all rooms
+    pub(crate) fn player_room(&self) -> usize {
+        let position = self.player.position;
+        self.level
+            .room_at((position.0 as f32, position.1 as f32))
+            .unwrap_or(0)
+    }
+
+    /// Advances the atmosphere by one tick and lets the player breathe the air of their room.
+    /// The atmosphere is created from the oxygen of the player if it does not fit the level.
+    pub(crate) fn update_atmosphere(&mut self) {
+        if self.level.rooms.is_empty() {
+            return;
+        }
+        if !self.atmosphere.fits(&self.level) {
+            self.atmosphere = Atmosphere::new(&self.level, self.player.resources.oxygen);
+        }
+        let room = self.player_room();
+        self.atmosphere.tick(
+            &self.level,
+            &self.machines,
+            room,
+            self.player.resources_change.oxygen,
+        );
+        self.player.resources.oxygen = self.atmosphere.rooms[room].oxygen;
+    }
+
+    /// Opens or seals the door next to the player. A door can not be sealed while the player
+    /// stands in it.
+    pub(crate) fn toggle_door(&mut self) {
+        let position = self.player.position;
+        let Some(index) = self.level.doors.iter().position(|door| {
+            let area = Rect::new(
+                door.area.x - PLAYER_INTERACTION_RADIUS,
+                door.area.y - PLAYER_INTERACTION_RADIUS,
+                door.area.w + PLAYER_INTERACTION_RADIUS * 2.,
+                door.area.h + PLAYER_INTERACTION_RADIUS * 2.,
+            );
+            is_colliding(position, &area) && !is_colliding(position, &door.area)
+        }) else {
+            return;
+        };
+        if let Some(open) = self.atmosphere.doors.get_mut(index) {
+            *open = !*open;
+            info!("Door {} is open: {}", index, open);
+        }
+    }
+
+    /// Returns whether a sealed door blocks the position
+    /// # Arguments
+    /// * `position` - The position of the player
+    pub(crate) fn is_sealed(&self, position: (usize, usize)) -> bool {
+        self.level
+            .doors
+            .iter()
+            .zip(&self.atmosphere.doors)
+            .any(|(door, open)| !open && is_colliding(position, &door.area))
+    }
+
+    /// Draws the doors and the pressure and oxygen of every room
+    /// # Arguments
+    /// * `canvas`: The canvas to draw on
+    /// * `scale`: The scale of the canvas
+    /// * `ctx`: The `Context` of the game
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the paint function
+    pub(crate) fn draw_atmosphere(
+        &self,
+        canvas: &mut Canvas,
+        scale: Vec2,
+        ctx: &mut Context,
+    ) -> RLResult {
+        for (door, open) in self.level.doors.iter().zip(&self.atmosphere.doors) {
+            let (mode, color) = if *open {
+                (DrawMode::stroke(3.), RLColor::GREY)
+            } else {
+                (DrawMode::fill(), RLColor::DARK_GREY)
+            };
+            let rect = Rect::new(0., 0., door.area.w, door.area.h);
+            let mesh = Mesh::new_rectangle(ctx, mode, rect, color)?;
+            draw!(canvas, &mesh, Vec2::new(door.area.x, door.area.y), scale);
+        }
+        // The only room of a level without rooms is described by the resource bars
+        if self.level.rooms.len() < 2 {
+            return Ok(());
+        }
+        let share = |value: u16| f32::from(value) / f32::from(u16::MAX) * 100.;
+        for (room, air) in self.level.rooms.iter().zip(&self.atmosphere.rooms) {
+            let text = Text::new(
+                TextFragment::new(format!(
+                    "{}\n{}: {:.0}%  {}: {:.0}%",
+                    tr!(&format!("room.{}", room.name)),
+                    tr!("hud.oxygen"),
+                    share(air.oxygen),
+                    tr!("room.pressure"),
+                    share(air.pressure)
+                ))
+                .color(RLColor::BLACK)
+                .scale(18.),
+            );
+            draw!(
+                canvas,
+                &text,
+                Vec2::new(room.area.x + 20., room.area.y + 210.),
+                scale
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LEVEL: &str = "background: basis.png
+spawn: [10, 10]
+walkable:
+  - [[0, 0], [300, 0], [300, 100], [0, 100]]
+rooms:
+  - { name: left, area: { x: 0.0, y: 0.0, w: 100.0, h: 100.0 } }
+  - { name: middle, area: { x: 100.0, y: 0.0, w: 100.0, h: 100.0 } }
+  - { name: right, area: { x: 200.0, y: 0.0, w: 100.0, h: 100.0 } }
+doors:
+  - { rooms: [left, middle], area: { x: 95.0, y: 0.0, w: 10.0, h: 100.0 } }
+  - { rooms: [middle, right], area: { x: 195.0, y: 0.0, w: 10.0, h: 100.0 }, open: false }
+machines: []
+";
+
+    #[test]
+    fn test_breathing_and_doors() {
+        let level = Level::parse("test.yaml", LEVEL).unwrap();
+        let mut atmosphere = Atmosphere::new(&level, 10_000);
+        assert_eq!(atmosphere.doors, vec![true, false]);
+        for _ in 0..100 {
+            atmosphere.tick(&level, &[], 0, -5);
+        }
+        // The air of the left room flows into the middle room, but not into the sealed right room
+        assert!(atmosphere.rooms[0].oxygen < atmosphere.rooms[1].oxygen);
+        assert!(atmosphere.rooms[1].oxygen < 10_000);
+        assert_eq!(atmosphere.rooms[2].oxygen, 10_000);
+        assert!(atmosphere.rooms.iter().all(|air| air.pressure == u16::MAX));
+    }
+
+    #[test]
+    fn test_atmosphere_fits_level() {
+        let level = Level::parse("test.yaml", LEVEL).unwrap();
+        assert!(!Atmosphere::default().fits(&level));
+        assert!(Atmosphere::new(&level, 0).fits(&level));
+        assert_eq!(level.room_at((250., 50.)), Some(2));
+        assert_eq!(level.room_at((350., 50.)), None);
+    }
+
+    #[test]
+    fn test_hole_drains_its_room() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        for _ in 0..100 {
+            gamestate.update_atmosphere();
+        }
+        let rooms = &gamestate.atmosphere.rooms;
+        // The hole is in the first room, the air of the others escapes through the open doors
+        assert!(rooms[0].pressure < rooms[1].pressure);
+        assert!(rooms[1].pressure < rooms[2].pressure);
+        assert_eq!(gamestate.player.resources.oxygen, rooms[0].oxygen);
+    }
+}
@@ -1,121 +1,142 @@
 //! Contains logic and structures for the game events.
 //! Author: ["Maximilian Floto", "Marion Hinkel", "Sander Stella", "Philipp Wolf"]
-use crate::backend::constants::{DESIRED_FPS, SANDSTURM_CR};
+use crate::backend::constants::DESIRED_FPS;
 use crate::backend::gamestate::GameState;
 use crate::backend::screen::{Popup, StackCommand};
+use crate::game_core::event_catalog::EventDefinition;
 use crate::game_core::resources::Resources;
-use crate::languages::german::{
-    INFORMATIONSPOPUP_MARS, INFORMATIONSPOPUP_NASA, KOMETENEINSCHLAG, SANDSTURM, STROMAUSFALL,
-};
-use crate::languages::german::{MARS_INFO, NASA_INFO, WARNINGS};
 use crate::machines::machine::State;
-use crate::RLResult;
-use ggez::graphics::Color;
-use ggez::Context;
+use crate::{tr, RLResult};
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::sync::mpsc::Sender;
 use tracing::info;
 
+/// Defines which kind of `Popup` is shown when an event starts
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PopupType {
+    Warning,
+    Nasa,
+    Mars,
+}
+
+/// only used for logging purposes
+impl Display for PopupType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PopupType::Warning => write!(f, "warning"),
+            PopupType::Nasa => write!(f, "nasa"),
+            PopupType::Mars => write!(f, "mars"),
+        }
+    }
+}
+
+/// Defines what an event does to the game when it starts
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum EventEffect {
+    /// Only changes the resources of the player while the event is active
+    #[default]
+    ResourceChange,
+    /// Opens one of the holes in the wall
+    HullBreach,
+    /// Stops the running power generator
+    PowerOutage,
+}
+
 /// defines an event which has an impact on the game and the player
 /// for example a popup or a change in the player's resources
 /// events can just fade off or stay as long as the player didnt interact with them
 
 /// Defines an event in the game
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub(crate) struct Event {
+pub struct Event {
     name: String,
     info_text: String,
     pub(crate) resources: Option<Resources<i16>>,
     duration: u32,
-    popup_type: String,
+    popup_type: PopupType,
     popup_message: String,
+    #[serde(default)]
+    effect: EventEffect,
 }
 
 impl Event {
     /// create new event
     /// # Arguments
-    /// * `event` - name and info text of the event
-    /// * `resources` - resources which are affected by the event
-    /// * `duration` - duration of the event in seconds
-    /// * `popup_type` - type of the popup which is shown when the event starts
+    /// * `definition` - The definition of the event in the event catalog
     /// * `popup_message` - message of the popup which is shown when the event starts
-    pub fn new(
-        event: [&str; 2],
-        popup_message: &str,
-        popup_type: &str,
-        resources: Option<Resources<i16>>,
-        duration: u32,
-    ) -> Self {
+    pub fn new(definition: &EventDefinition, popup_message: &str) -> Self {
         info!(
             "New event created: {}, info text: {}",
-            event[0].to_string(),
-            event[1].to_string()
+            definition.name,
+            tr!(&definition.info_text)
         );
         Self {
-            name: event[0].to_string(),
-            info_text: event[1].to_string(),
-            resources,
-            duration: duration * DESIRED_FPS,
-            popup_type: popup_type.to_string(),
+            name: definition.name.clone(),
+            info_text: definition.info_text.clone(),
+            resources: definition.resources,
+            duration: definition.duration * DESIRED_FPS,
+            popup_type: definition.popup_type,
             popup_message: popup_message.to_string(),
+            effect: definition.effect,
         }
     }
 
-    /// if no Event is active it either chooses a random event of the Event enum or nothing every 60 seconds
-    pub fn event_generator() -> Option<Event> {
-        let rng = fastrand::Rng::new();
-        let event = rng.usize(..15);
-        match event {
-            8 => Some(Event::new(
-                SANDSTURM,
-                WARNINGS[2],
-                "warning",
-                Some(SANDSTURM_CR),
-                5,
-            )),
-            0 | 3 => Some(Event::new(
-                KOMETENEINSCHLAG,
-                WARNINGS[0],
-                "warning",
-                None,
-                0,
-            )),
-            1 => Some(Event::new(
-                INFORMATIONSPOPUP_NASA,
-                NASA_INFO[rng.usize(..4)],
-                "nasa",
-                None,
-                0,
-            )),
-            2 | 9 | 7 => Some(Event::new(STROMAUSFALL, WARNINGS[1], "warning", None, 0)),
-            4 => Some(Event::new(
-                INFORMATIONSPOPUP_MARS,
-                MARS_INFO[rng.usize(..5)],
-                "mars",
-                None,
-                0,
-            )),
-            _ => None,
+    /// Rolls the event catalog of the gamestate and returns the chosen event, if any.
+    /// Every event is chosen according to its weight, but events which are on cooldown or need a
+    /// higher milestone are skipped, so that nothing happens instead.
+    /// # Arguments
+    /// * `gamestate` - The gamestate containing the event catalog and the event cooldowns
+    /// # Returns
+    /// * `Option<Event>` - The chosen event or `None` if no event occurs
+    pub fn event_generator(gamestate: &mut GameState) -> Option<Event> {
+        let total_weight = gamestate.event_catalog.total_weight();
+        if total_weight == 0 {
+            return None;
         }
+        let mut roll = gamestate.rng.usize(..total_weight);
+        let definition = gamestate.event_catalog.events.iter().find(|event| {
+            if roll < event.weight {
+                true
+            } else {
+                roll -= event.weight;
+                false
+            }
+        })?;
+        let time = gamestate.player.time;
+        let on_cooldown = gamestate
+            .event_cooldowns
+            .get(&definition.name)
+            .map_or(false, |ready_at| *ready_at > time);
+        if on_cooldown || gamestate.player.milestone < definition.min_milestone {
+            return None;
+        }
+        gamestate.event_cooldowns.insert(
+            definition.name.clone(),
+            time + definition.cooldown * DESIRED_FPS,
+        );
+        let message = &definition.messages[gamestate.rng.usize(..definition.messages.len())];
+        Some(Event::new(definition, message))
     }
 
     /// Sends a popup of an event to the screen
     /// # Arguments
-    /// * `popup_message` - The message which should be displayed in the popup
+    /// * `popup_message` - The message id of the message which should be displayed in the popup
     /// * `sender` - The sender which is used to send the popup to the screen
     /// * `popup_type` - The type of the popup, which is used to determine the color of the popup
     /// * `event_name` - The name of the event, which is used to determine what Event name should be displayed in the popup
     pub fn send_popup(
         popup_message: &str,
         sender: &Sender<StackCommand>,
-        popup_type: &str,
+        popup_type: PopupType,
         event_name: &str,
     ) -> RLResult {
         let popup = match popup_type {
-            "warning" => Popup::warning(popup_message.to_string()),
-            "nasa" => Popup::nasa(popup_message.to_string()),
-            "mars" => Popup::mars(popup_message.to_string()),
-            _ => Popup::new(Color::RED, "Error".to_string(), 10),
+            PopupType::Warning => Popup::warning(tr!(popup_message)),
+            PopupType::Nasa => Popup::nasa(tr!(popup_message)),
+            PopupType::Mars => Popup::mars(tr!(popup_message)),
         };
         sender.send(StackCommand::Popup(popup))?;
         info!(
@@ -137,25 +158,23 @@ impl Event {
     /// * `restore` - If true the event will be deactivated and the resources will be restored
     /// * `gamestate` - The gamestate which is used to access the player and the machines
     pub fn action(&self, restore: bool, gamestate: &mut GameState) -> RLResult {
-        const KOMETENEINSCHLAG_NAME: &str = KOMETENEINSCHLAG[0];
-        const STROMAUSFALL_NAME: &str = STROMAUSFALL[0];
         let sender = gamestate.get_screen_sender()?.clone();
 
         // handle event effects
-        match self.name.as_str() {
-            KOMETENEINSCHLAG_NAME => {
+        match self.effect {
+            EventEffect::HullBreach => {
                 if let Some(one_hole) = gamestate
                     .machines
                     .iter_mut()
                     .find(|machine| machine.name == "Loch" && machine.state != State::Running)
                 {
                     // event not triggered if both machine are already running
-                    Event::send_popup(&self.popup_message, &sender, &self.popup_type, &self.name)
+                    Event::send_popup(&self.popup_message, &sender, self.popup_type, &self.name)
                         .unwrap();
                     one_hole.change_state_to(&State::Running);
                 }
             }
-            STROMAUSFALL_NAME => {
+            EventEffect::PowerOutage => {
                 gamestate.machines.iter_mut().for_each(|machine| {
                     // if machine is running it will b use tracing::{info, Id};e stopped
                     // event not triggered if machine is broken or idling
@@ -163,7 +182,7 @@ impl Event {
                         Event::send_popup(
                             &self.popup_message,
                             &sender,
-                            &self.popup_type,
+                            self.popup_type,
                             &self.name,
                         )
                         .unwrap();
@@ -171,9 +190,9 @@ impl Event {
                     }
                 });
             }
-            // apply direct resource changes if there are any and the event is not handled above
-            _ => {
-                Event::send_popup(&self.popup_message, &sender, &self.popup_type, &self.name)?;
+            // apply direct resource changes if there are any
+            EventEffect::ResourceChange => {
+                Event::send_popup(&self.popup_message, &sender, self.popup_type, &self.name)?;
                 if let Some(resources) = self.resources {
                     if restore {
                         gamestate.player.resources_change =
@@ -196,10 +215,10 @@ impl Event {
 
     /// Deletes due events from the gamestates events vector and adds new events
     /// # Arguments
-    /// * `gamestate` - The gamestate which is used to access the events vector
-    /// * `context` - The game context which is used to access the current tick
-    pub fn update_events(ctx: &Context, gamestate: &mut GameState) -> RLResult {
-        if ctx.time.ticks() % 20 == 0 {
+    /// * `gamestate` - The gamestate which is used to access the events vector and the current tick
+    pub fn update_events(gamestate: &mut GameState) -> RLResult {
+        let ticks = gamestate.player.time;
+        if ticks % 20 == 0 {
             gamestate.events.iter_mut().for_each(|event| {
                 event.duration = event.duration.saturating_sub(20);
                 if event.name == "Sandsturm" {}
@@ -213,21 +232,26 @@ impl Event {
                     }
                 }
             }
-            // remove all events which are not active anymore
+            // remove all events which are not active anymore and remember them for the objectives
+            let time = gamestate.player.time;
             gamestate.events.retain(|event| {
                 if event.is_active() {
                     true
                 } else {
                     info!("Event {} is not active anymore", event.get_name());
+                    gamestate
+                        .objectives
+                        .survived_events
+                        .insert(event.get_name(), time);
                     false
                 }
             });
         }
         // have a maximum of one active event
-        if ctx.time.ticks() >= 400 && ctx.time.ticks() % 200 == 0 {
+        if ticks >= 400 && ticks % 200 == 0 {
             // generate new event
             // might not return an event
-            let gen_event = Event::event_generator();
+            let gen_event = Event::event_generator(gamestate);
             // if event is not none, add it to the gamestates events vector and activate apply its effect
             if let Some(event) = gen_event {
                 event.action(false, gamestate)?;
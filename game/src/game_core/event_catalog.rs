@@ -0,0 +1,168 @@
+//! Contains the event catalog, which describes all events of the game and how often they occur.
+use crate::backend::error::create_data_error;
+use crate::game_core::event::{EventEffect, PopupType};
+use crate::game_core::resources::Resources;
+use crate::RLResult;
+use serde::Deserialize;
+use std::fs;
+use tracing::info;
+
+/// Contains all events which can occur in the game
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventCatalog {
+    /// The weight of the outcome that no event occurs
+    pub(crate) no_event_weight: usize,
+    /// All events which can occur
+    pub events: Vec<EventDefinition>,
+}
+
+/// Describes a single event as it is written in the event catalog
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventDefinition {
+    pub name: String,
+    /// The message id of the text describing the event
+    pub(crate) info_text: String,
+    /// The relative chance of this event to be chosen
+    pub(crate) weight: usize,
+    /// Seconds after the start of this event until it can occur again
+    #[serde(default)]
+    pub(crate) cooldown: u32,
+    /// The milestone the player needs to have reached for this event to occur
+    #[serde(default)]
+    pub(crate) min_milestone: usize,
+    /// Seconds the resource change of this event stays active
+    #[serde(default)]
+    pub(crate) duration: u32,
+    /// Resources which are affected by the event
+    #[serde(default)]
+    pub(crate) resources: Option<Resources<i16>>,
+    /// The kind of popup which is shown when the event starts
+    pub(crate) popup_type: PopupType,
+    /// The message ids of which one is shown in the popup when the event starts
+    pub(crate) messages: Vec<String>,
+    #[serde(default)]
+    pub(crate) effect: EventEffect,
+}
+
+impl EventCatalog {
+    /// Loads the event catalog at the given path.
+    /// # Arguments
+    /// * `path` - The path of the event catalog file
+    /// # Returns
+    /// * `RLResult<EventCatalog>` - The validated catalog or an error describing the invalid entry
+    pub(crate) fn load(path: &str) -> RLResult<Self> {
+        info!("Loading event catalog from {}", path);
+        let source = fs::read_to_string(path).map_err(|e| create_data_error(path, "file", e))?;
+        Self::parse(path, &source)
+    }
+
+    /// Parses and validates the content of an event catalog.
+    /// # Arguments
+    /// * `file` - The name of the file the content was read from, used for error messages
+    /// * `source` - The content of the event catalog
+    /// # Returns
+    /// * `RLResult<EventCatalog>` - The validated catalog or an error describing the invalid entry
+    pub(crate) fn parse(file: &str, source: &str) -> RLResult<Self> {
+        let catalog: EventCatalog =
+            serde_yaml::from_str(source).map_err(|e| create_data_error(file, "events", e))?;
+        catalog.validate(file)?;
+        Ok(catalog)
+    }
+
+    /// Checks that every event can be used by the event generator
+    /// # Arguments
+    /// * `file` - The catalog file, used for error messages
+    fn validate(&self, file: &str) -> RLResult {
+        for (i, event) in self.events.iter().enumerate() {
+            let field = format!("events[{i}]");
+            if event.name.trim().is_empty() {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.name"),
+                    "the name must not be empty",
+                ));
+            }
+            if event.weight == 0 {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.weight"),
+                    "the weight must be greater than zero",
+                ));
+            }
+            if event.messages.is_empty() {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.messages"),
+                    "an event needs at least one message",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the sum of all weights including the weight of no event
+    pub(crate) fn total_weight(&self) -> usize {
+        self.no_event_weight + self.events.iter().map(|e| e.weight).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::constants::DESIRED_FPS;
+    use crate::backend::error::RLError;
+    use crate::backend::gamestate::GameState;
+    use crate::game_core::event::Event;
+
+    const CATALOG: &str = include_str!("../../../assets/data/events.yaml");
+
+    #[test]
+    fn test_parse_event_catalog() {
+        let catalog = EventCatalog::parse("events.yaml", CATALOG).unwrap();
+        assert_eq!(catalog.events.len(), 5);
+        assert_eq!(catalog.total_weight(), 15);
+    }
+
+    #[test]
+    fn test_unknown_popup_type() {
+        let source = "no_event_weight: 1
+events:
+  - name: Test
+    info_text: Test
+    weight: 1
+    popup_type: alien
+    messages: [Test]
+";
+        match EventCatalog::parse("test.yaml", source) {
+            Err(RLError::InvalidData(message)) => assert!(
+                message.contains("events[0].popup_type: unknown variant `alien`"),
+                "{message}"
+            ),
+            other => panic!("Expected an InvalidData error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_event_cooldown_and_milestone() {
+        let source = "no_event_weight: 0
+events:
+  - name: Test
+    info_text: Test
+    weight: 1
+    cooldown: 10
+    min_milestone: 1
+    popup_type: nasa
+    messages: [Test]
+";
+        let mut gamestate = GameState::default();
+        gamestate.event_catalog = EventCatalog::parse("test.yaml", source).unwrap();
+        assert!(Event::event_generator(&mut gamestate).is_none());
+        gamestate.player.milestone = 1;
+        assert!(Event::event_generator(&mut gamestate).is_some());
+        assert!(Event::event_generator(&mut gamestate).is_none());
+        gamestate.player.time += 10 * DESIRED_FPS;
+        assert!(Event::event_generator(&mut gamestate).is_some());
+    }
+}
@@ -0,0 +1,237 @@
+//! Contains the items lying on the floor of the habitat, which the player dropped or could not carry.
+use crate::backend::constants::{PLAYER_ICON_SIZE, PLAYER_INTERACTION_RADIUS};
+use crate::backend::gamestate::GameState;
+use crate::backend::rlcolor::RLColor;
+use crate::backend::screen::{Popup, StackCommand};
+use crate::backend::utils::{get_draw_params, is_colliding};
+use crate::{draw, tr, RLResult};
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Rect, Text, TextFragment};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// A pile of items lying on the floor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FloorItem {
+    /// The id of the item
+    pub id: String,
+    /// The amount of items in the pile
+    pub amount: i32,
+    /// The position of the pile on the map
+    pub(crate) position: (usize, usize),
+}
+
+impl FloorItem {
+    /// Returns whether the player at a position can pick up the pile
+    /// # Arguments
+    /// * `position` - The position of the player
+    fn is_reachable(&self, position: (usize, usize)) -> bool {
+        let area = Rect::new(
+            self.position.0 as f32 - PLAYER_INTERACTION_RADIUS,
+            self.position.1 as f32 - PLAYER_INTERACTION_RADIUS,
+            PLAYER_INTERACTION_RADIUS * 2.,
+            PLAYER_INTERACTION_RADIUS * 2.,
+        );
+        is_colliding(position, &area)
+    }
+}
+
+impl GameState {
+    /// Puts items on the floor at the feet of the player
+    /// # Arguments
+    /// * `id` - The id of the item
+    /// * `amount` - The amount of items
+    pub(crate) fn put_on_floor(&mut self, id: &str, amount: i32) {
+        if amount <= 0 {
+            return;
+        }
+        let position = (
+            self.player.position.0 + PLAYER_ICON_SIZE.0 / 2,
+            self.player.position.1 + PLAYER_ICON_SIZE.1 - 20,
+        );
+        info!("{} {} put on the floor at {:?}", amount, id, position);
+        if let Some(pile) = self
+            .floor_items
+            .iter_mut()
+            .find(|pile| pile.id == id && pile.position == position)
+        {
+            pile.amount += amount;
+        } else {
+            self.floor_items.push(FloorItem {
+                id: id.to_string(),
+                amount,
+                position,
+            });
+        }
+    }
+
+    /// Drops one unit of an item of the inventory on the floor
+    /// # Arguments
+    /// * `slot` - The place of the item in the inventory, starting at 0
+    pub(crate) fn drop_item(&mut self, slot: usize) {
+        let Some((id, amount)) = self.player.inventory.get(slot).cloned() else {
+            return;
+        };
+        let Some(item) = self.item_registry.get(&id) else {
+            return;
+        };
+        if amount <= 0 {
+            return;
+        }
+        self.player.add_item(item, -1);
+        self.put_on_floor(&id, 1);
+    }
+
+    /// Picks up as many items lying next to the player as they can carry.
+    /// If they can not carry any of them and there is no machine next to them, the player is
+    /// informed instead.
+    /// # Returns
+    /// * `RLResult<bool>` - Whether the player picked up items or was informed, otherwise the
+    ///   player interacts with the machine or door next to them
+    pub(crate) fn pick_up_items(&mut self) -> RLResult<bool> {
+        let position = self.player.position;
+        let mut reachable = false;
+        let mut picked_up = false;
+        for pile in &mut self.floor_items {
+            if !pile.is_reachable(position) {
+                continue;
+            }
+            reachable = true;
+            let Some(item) = self.item_registry.get(&pile.id) else {
+                continue;
+            };
+            let amount = pile
+                .amount
+                .min(self.player.room_for(item, &self.item_registry));
+            if amount > 0 {
+                info!("Picked up {} {}", amount, pile.id);
+                self.player.add_item(item, amount);
+                pile.amount -= amount;
+                picked_up = true;
+            }
+        }
+        self.floor_items.retain(|pile| pile.amount > 0);
+        if picked_up {
+            return Ok(true);
+        }
+        if !reachable
+            || self
+                .machines
+                .iter()
+                .any(|machine| machine.is_interactable(position))
+        {
+            return Ok(false);
+        }
+        let popup = Popup::warning(tr!("inventory.full"));
+        self.get_screen_sender()?.send(StackCommand::Popup(popup))?;
+        Ok(true)
+    }
+
+    /// Draws the items lying on the floor with their amount
+    /// # Arguments
+    /// * `canvas`: The canvas to draw on
+    /// * `scale`: The scale of the canvas
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the paint function
+    pub(crate) fn draw_floor_items(&self, canvas: &mut Canvas, scale: Vec2) -> RLResult {
+        for pile in &self.floor_items {
+            let Some(item) = self.item_registry.get(&pile.id) else {
+                continue;
+            };
+            let position = Vec2::new(pile.position.0 as f32, pile.position.1 as f32);
+            draw!(canvas, self.get_asset(&item.img)?, position, scale);
+            if pile.amount > 1 {
+                let text = Text::new(
+                    TextFragment::new(format!("{}", pile.amount))
+                        .color(RLColor::BLACK)
+                        .scale(18.),
+                );
+                draw!(
+                    canvas,
+                    &text,
+                    Vec2::new(position.x, position.y - 18.),
+                    scale
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::movement::Input;
+    use crate::game_core::player::Player;
+
+    #[test]
+    fn test_drop_and_pick_up() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        let slot = gamestate
+            .player
+            .inventory
+            .iter()
+            .position(|(id, _)| id == "benzin")
+            .unwrap();
+        let benzin = gamestate.player.get_item_amount("benzin");
+        let drop = Input {
+            drop_item: Some(slot),
+            ..Input::default()
+        };
+        gamestate.move_player(drop).unwrap();
+        gamestate.move_player(drop).unwrap();
+        assert_eq!(gamestate.player.get_item_amount("benzin"), benzin - 2);
+        assert_eq!(gamestate.floor_items.len(), 1);
+        assert_eq!(gamestate.floor_items[0].amount, 2);
+        // The dropped items are saved with the game
+        let saved = serde_yaml::to_string(&gamestate).unwrap();
+        let loaded: GameState = serde_yaml::from_str(&saved).unwrap();
+        assert_eq!(loaded.floor_items, gamestate.floor_items);
+
+        gamestate
+            .move_player(Input {
+                interact: true,
+                ..Input::default()
+            })
+            .unwrap();
+        assert_eq!(gamestate.player.get_item_amount("benzin"), benzin);
+        assert!(gamestate.floor_items.is_empty());
+    }
+
+    #[test]
+    fn test_full_inventory_interacts_with_machine() {
+        let (mut gamestate, receiver) = GameState::test_game(1);
+        let index = gamestate.machine_index("Stromgenerator");
+        gamestate.stand_next_to(index);
+        gamestate.player = Player {
+            position: gamestate.player.position,
+            ..Player::default()
+        };
+        let benzin = gamestate.item_registry.get("benzin").unwrap().clone();
+        gamestate.add_items(&[("benzin".to_string(), benzin.stack_limit + 2)]);
+        assert_eq!(
+            gamestate.player.get_item_amount("benzin"),
+            benzin.stack_limit
+        );
+        assert_eq!(gamestate.floor_items[0].amount, 2);
+        // The player can not pick up the items, so E interacts with the machine next to them
+        gamestate
+            .move_player(Input {
+                interact: true,
+                ..Input::default()
+            })
+            .unwrap();
+        assert_eq!(gamestate.floor_items[0].amount, 2);
+        assert!(gamestate.machines[index].is_busy());
+        assert!(receiver
+            .try_iter()
+            .all(|command| !matches!(command, StackCommand::Popup(_))));
+        // Away from any machine the player is told that they can not carry the items
+        gamestate.player.position = (0, 0);
+        gamestate.put_on_floor("benzin", 1);
+        assert!(gamestate.pick_up_items().unwrap());
+        assert!(receiver
+            .try_iter()
+            .any(|command| matches!(command, StackCommand::Popup(_))));
+    }
+}
@@ -1,15 +1,14 @@
 //! Contains logic for our infoscreens, notably the tutorial screen and the deathscreen.
 //! Author: ["Benedikt Brandmaier", "Maximilian Floto", "Marion Hinkel", "Leo Schnüll", "Philipp Wolf"]
-use crate::backend::gamestate::{GameCommand, GameState};
+use crate::backend::constants::DEFAULT_LEVEL_PATH;
+use crate::backend::game_data::GameData;
+use crate::backend::gamestate::GameState;
+use crate::backend::options::Options;
 use crate::backend::screen::{Screen, StackCommand};
 use crate::backend::utils::{get_draw_params, get_scale};
-use crate::languages::german::{
-    ADDITIONAL_INFO_STRING, AIR_AND_ENERGY_STRING, AIR_STRING, BUTTON_INFO, DEATH_REASON_STRING,
-    ENERGY_STRING, INTRO_TEXT, TUTORIAL_TEXT, WINNING_TEXT,
-};
 
 use crate::main_menu::mainmenu::MainMenu;
-use crate::{draw, RLResult};
+use crate::{draw, tr, RLResult};
 use ggez::glam::Vec2;
 use ggez::winit::event::VirtualKeyCode;
 use ggez::{graphics, Context};
@@ -28,9 +27,9 @@ pub enum DeathReason {
 impl Display for DeathReason {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            DeathReason::Oxygen => write!(f, "{AIR_STRING}"),
-            DeathReason::Energy => write!(f, "{ENERGY_STRING}"),
-            DeathReason::Both => write!(f, "{AIR_AND_ENERGY_STRING}"),
+            DeathReason::Oxygen => write!(f, "{}", tr!("death.oxygen")),
+            DeathReason::Energy => write!(f, "{}", tr!("death.energy")),
+            DeathReason::Both => write!(f, "{}", tr!("death.both")),
         }
     }
 }
@@ -51,6 +50,8 @@ pub struct InfoScreen {
     sender: Sender<StackCommand>,
     screentype: ScreenType,
     background_image: Option<graphics::Image>,
+    /// The command line options, which are handed on to the next game
+    options: Options,
 }
 
 impl InfoScreen {
@@ -58,12 +59,17 @@ impl InfoScreen {
     /// # Arguments
     /// * `death_reason` - The reason for the death of the player
     /// * `sender` - The sender to send the command to the `ScreenStack`
-    pub fn new_deathscreen(death_reason: DeathReason, sender: Sender<StackCommand>) -> Self {
+    /// * `options` - The command line options, which are handed on to the next game
+    pub(crate) fn new_deathscreen(
+        death_reason: DeathReason,
+        sender: Sender<StackCommand>,
+        options: Options,
+    ) -> Self {
         info!("The player died due to a lack of : {:?}", death_reason);
 
-        let mut main_message = graphics::Text::new(format!("{DEATH_REASON_STRING} {death_reason}"));
+        let mut main_message = graphics::Text::new(tr!("screen.death", reason = death_reason));
         main_message.set_scale(70.);
-        let mut additional_text = graphics::Text::new(ADDITIONAL_INFO_STRING);
+        let mut additional_text = graphics::Text::new(tr!("screen.press_escape"));
         additional_text.set_scale(70.);
         let background = "deathscreen".to_string();
         let screentype = ScreenType::Death;
@@ -74,15 +80,17 @@ impl InfoScreen {
             sender,
             screentype,
             background_image: None,
+            options,
         }
     }
     /// Creates a new `IntroScreen` using `InfoScreen`
     /// # Arguments
     /// * `sender` - The sender to send the command to the `ScreenStack`
-    pub fn new_introscreen(sender: Sender<StackCommand>) -> Self {
-        let mut main_message = graphics::Text::new(format!("{INTRO_TEXT} \n{TUTORIAL_TEXT}"));
+    /// * `options` - The command line options, which decide the seed of the new game and if it is recorded
+    pub(crate) fn new_introscreen(sender: Sender<StackCommand>, options: Options) -> Self {
+        let mut main_message = graphics::Text::new(tr!("screen.intro"));
         main_message.set_scale(50.);
-        let mut additional_text = graphics::Text::new(BUTTON_INFO);
+        let mut additional_text = graphics::Text::new(tr!("screen.press_space"));
         additional_text.set_scale(50.);
         let background = "Introscreen".to_string();
         let screentype = ScreenType::Intro;
@@ -93,15 +101,17 @@ impl InfoScreen {
             sender,
             screentype,
             background_image: None,
+            options,
         }
     }
     /// Creates a new Winning using `InfoScreen`
     /// # Arguments
     /// * `sender` - The sender to send the command to the `ScreenStack`
-    pub fn new_winningscreen(sender: Sender<StackCommand>) -> Self {
-        let mut main_message = graphics::Text::new(WINNING_TEXT);
+    /// * `options` - The command line options, which are handed on to the next game
+    pub(crate) fn new_winningscreen(sender: Sender<StackCommand>, options: Options) -> Self {
+        let mut main_message = graphics::Text::new(tr!("screen.winning"));
         main_message.set_scale(70.);
-        let mut additional_text = graphics::Text::new(ADDITIONAL_INFO_STRING);
+        let mut additional_text = graphics::Text::new(tr!("screen.press_escape"));
         additional_text.set_scale(70.);
         let background = "Winningscreen".to_string();
         let screentype = ScreenType::Winning;
@@ -112,6 +122,7 @@ impl InfoScreen {
             sender,
             screentype,
             background_image: None,
+            options,
         }
     }
 }
@@ -135,14 +146,11 @@ impl Screen for InfoScreen {
             (ScreenType::Intro, Some(&VirtualKeyCode::Space)) => {
                 self.sender.send(StackCommand::Pop)?;
                 self.sender.send(StackCommand::Push(Box::new({
-                    let mut gamestate = GameState::new(ctx)?;
-                    gamestate.init(ctx)?;
-                    gamestate.create_machine();
-                    gamestate
-                        .sender
-                        .as_mut()
-                        .unwrap()
-                        .send(GameCommand::Milestone)?;
+                    let seed = self.options.seed.unwrap_or_else(|| fastrand::u64(..));
+                    let mut gamestate =
+                        GameState::new_game(GameData::load(DEFAULT_LEVEL_PATH)?, seed)?;
+                    gamestate.load_assets(ctx)?;
+                    gamestate.set_options(self.options.clone())?;
                     gamestate
                 })))?;
             }
@@ -153,6 +161,7 @@ impl Screen for InfoScreen {
                 self.sender.send(StackCommand::Pop)?;
                 self.sender.send(StackCommand::Push(Box::new(MainMenu::new(
                     self.sender.clone(),
+                    self.options.clone(),
                 ))))?;
             }
             _ => {}
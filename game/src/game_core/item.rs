@@ -1,29 +1,171 @@
-//! TODO
+//! Contains the items of the game and the registry they are loaded from.
 //! Author: ["Marion Hinkel"]
-use serde::{Deserialize, Serialize};
+use crate::backend::error::create_data_error;
+use crate::{tr, RLResult};
+use serde::Deserialize;
+use std::fs;
 use tracing::info;
 
-/// Defines an item in the inventory of the player
-/// Contains the name of the item, information about the item and the image
-#[derive(Clone, Default, Eq, Debug, PartialEq, Serialize, Deserialize)]
+/// Defines an item which can be in the inventory of the player
+/// Contains the id, the name of the item, information about the item and the image
+#[derive(Clone, Eq, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Item {
+    /// The stable id used to reference the item in inventories, trades and saves
+    pub id: String,
+    /// The message id of the name shown to the player
     pub name: String,
-    info_text: String,
+    /// The message id of the information shown when hovering over the item in the inventory
+    pub info_text: String,
+    /// The file name of the icon in the assets folder
     pub img: String,
+    /// The maximum amount of this item the player can carry
+    pub stack_limit: i32,
+    /// The weight of one unit, the player can carry `MAX_CARRY_WEIGHT` in total
+    #[serde(default)]
+    pub weight: i32,
+    /// The amount of this item the player starts a new game with
+    #[serde(default)]
+    pub start_amount: i32,
 }
-impl Item {
-    /// Create new `Item`
+
+/// Contains all items of the game
+#[derive(Clone, Default, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemRegistry {
+    items: Vec<Item>,
+}
+
+impl ItemRegistry {
+    /// Loads the item registry at the given path.
     /// # Arguments
-    /// * `item` - 3 long String-Array, which defines the name, info text and image path for the created item
-    pub(crate) fn new(item: [&str; 3]) -> Self {
-        info!(
-            "New Item created: name: {}, info_text: {}, img path: {}",
-            item[0], item[1], item[2]
-        );
-        Self {
-            name: item[0].to_string(),
-            info_text: item[1].to_string(),
-            img: item[2].to_string(),
+    /// * `path` - The path of the item registry file
+    /// # Returns
+    /// * `RLResult<ItemRegistry>` - The validated registry or an error describing the invalid entry
+    pub(crate) fn load(path: &str) -> RLResult<Self> {
+        info!("Loading item registry from {}", path);
+        let source = fs::read_to_string(path).map_err(|e| create_data_error(path, "file", e))?;
+        Self::parse(path, &source)
+    }
+
+    /// Parses and validates the content of an item registry.
+    /// # Arguments
+    /// * `file` - The name of the file the content was read from, used for error messages
+    /// * `source` - The content of the item registry
+    /// # Returns
+    /// * `RLResult<ItemRegistry>` - The validated registry or an error describing the invalid entry
+    pub(crate) fn parse(file: &str, source: &str) -> RLResult<Self> {
+        let registry: ItemRegistry =
+            serde_yaml::from_str(source).map_err(|e| create_data_error(file, "items", e))?;
+        for (i, item) in registry.items.iter().enumerate() {
+            let field = format!("items[{i}]");
+            if item.id.is_empty() || item.id.contains(char::is_whitespace) {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.id"),
+                    "the id must not be empty or contain whitespace",
+                ));
+            }
+            if registry.items[..i].iter().any(|other| other.id == item.id) {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.id"),
+                    format!("the id \"{}\" is used more than once", item.id),
+                ));
+            }
+            if item.stack_limit <= 0 {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.stack_limit"),
+                    "the stack limit must be greater than zero",
+                ));
+            }
+            if item.weight < 0 {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.weight"),
+                    "the weight must not be negative",
+                ));
+            }
+            if !(0..=item.stack_limit).contains(&item.start_amount) {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.start_amount"),
+                    "the start amount must be between zero and the stack limit",
+                ));
+            }
         }
+        Ok(registry)
+    }
+
+    /// Returns the item with the given id
+    /// # Arguments
+    /// * `id` - The id of the item
+    /// # Returns
+    /// * `Option<&Item>` - The item or `None` if there is no item with this id
+    pub fn get(&self, id: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    /// Returns the translated name of the item with the given id, or the id itself if the item is unknown
+    pub(crate) fn name(&self, id: &str) -> String {
+        self.get(id).map_or(id.to_string(), |item| tr!(&item.name))
+    }
+
+    /// Returns the total weight of items
+    /// # Arguments
+    /// * `items` - The ids and amounts of the items, unknown items weigh nothing
+    pub fn weight(&self, items: &[(String, i32)]) -> i32 {
+        items
+            .iter()
+            .filter_map(|(id, amount)| Some(self.get(id)?.weight * amount))
+            .sum()
+    }
+
+    /// Returns the inventory a player starts a new game with
+    /// # Returns
+    /// * `Vec<(String, i32)>` - The id and start amount of every item
+    pub(crate) fn starting_inventory(&self) -> Vec<(String, i32)> {
+        self.items
+            .iter()
+            .map(|item| (item.id.clone(), item.start_amount))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::error::RLError;
+    use crate::languages::locale::use_english;
+
+    const REGISTRY: &str = include_str!("../../../assets/data/items.yaml");
+
+    #[test]
+    fn test_parse_item_registry() {
+        let registry = ItemRegistry::parse("items.yaml", REGISTRY).unwrap();
+        use_english();
+        assert_eq!(registry.name("benzin"), "Petrol");
+        assert_eq!(registry.name("unknown"), "unknown");
+        assert_eq!(
+            registry.starting_inventory(),
+            vec![
+                ("super_glue".to_string(), 0),
+                ("benzin".to_string(), 3),
+                ("printed_part".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_item_id() {
+        let source = "items:
+  - { id: glue, name: A, info_text: A, img: A.png, stack_limit: 1 }
+  - { id: glue, name: B, info_text: B, img: B.png, stack_limit: 1 }
+";
+        assert!(matches!(
+            ItemRegistry::parse("test.yaml", source),
+            Err(RLError::InvalidData(_))
+        ));
     }
 }
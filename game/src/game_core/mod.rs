@@ -1,5 +1,9 @@
+pub(crate) mod atmosphere;
 pub(crate) mod event;
+pub(crate) mod event_catalog;
+pub(crate) mod floor_item;
 pub(crate) mod infoscreen;
 pub(crate) mod item;
+pub(crate) mod objective;
 pub(crate) mod player;
 pub(crate) mod resources;
@@ -0,0 +1,287 @@
+//! Contains the objective graph, which describes the campaign the player has to complete.
+use crate::backend::constants::DESIRED_FPS;
+use crate::backend::error::create_data_error;
+use crate::game_core::item::ItemRegistry;
+use crate::game_core::player::Player;
+use crate::game_core::resources::Resources;
+use crate::machines::machine::{Machine, State};
+use crate::RLResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use tracing::info;
+
+/// Contains all objectives of the campaign
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObjectiveCatalog {
+    /// The id of the objective the campaign starts with
+    pub(crate) start: String,
+    pub(crate) objectives: Vec<Objective>,
+}
+
+/// Describes a single objective of the campaign
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Objective {
+    pub(crate) id: String,
+    /// All conditions need to be fulfilled to complete the objective
+    #[serde(default)]
+    pub(crate) conditions: Conditions,
+    /// Granted once the objective is completed
+    #[serde(default)]
+    pub(crate) rewards: Rewards,
+    /// Maps trade names to message ids shown when the trade finishes while the objective is active
+    #[serde(default)]
+    pub(crate) hints: BTreeMap<String, String>,
+    /// The message ids shown in the handbook while the objective is active
+    #[serde(default)]
+    pub(crate) handbook: Vec<String>,
+    /// The ids of the objectives which are activated once this one is completed
+    #[serde(default)]
+    pub(crate) next: Vec<String>,
+}
+
+/// The conditions of an `Objective`
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Conditions {
+    /// Names of machines which must not be broken
+    #[serde(default)]
+    pub(crate) machines_repaired: Vec<String>,
+    /// Names of machines which must be running
+    #[serde(default)]
+    pub(crate) machines_running: Vec<String>,
+    /// Item ids and the amount the player needs to own
+    #[serde(default)]
+    pub(crate) items_owned: BTreeMap<String, i32>,
+    /// Seconds the player needs to survive after the objective was activated
+    #[serde(default)]
+    pub(crate) time_survived: u32,
+    /// Name of an event which has to end after the objective was activated
+    #[serde(default)]
+    pub(crate) event_survived: Option<String>,
+    /// Name of a trade which has to finish after the objective was activated
+    #[serde(default)]
+    pub(crate) trade_completed: Option<String>,
+}
+
+/// The rewards of an `Objective`
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Rewards {
+    /// Replaces the change rates of the player's resources
+    #[serde(default)]
+    pub(crate) set_resources_change: Option<Resources<i16>>,
+    /// Item ids and the amount the player receives
+    #[serde(default)]
+    pub(crate) items: BTreeMap<String, i32>,
+    /// The message id of a message shown to the player
+    #[serde(default)]
+    pub(crate) popup: Option<String>,
+    /// Whether the player wins the game
+    #[serde(default)]
+    pub(crate) win: bool,
+}
+
+/// The progress of the player in the objective graph, this is stored in the save
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectiveProgress {
+    /// The ids of the active objectives and the time (in ticks) they were activated
+    pub active: Vec<(String, u32)>,
+    /// The ids of the completed objectives
+    pub completed: Vec<String>,
+    /// The names of finished trades and the time (in ticks) each of them finished last
+    pub(crate) completed_trades: BTreeMap<String, u32>,
+    /// The names of ended events and the time (in ticks) each of them ended last
+    pub(crate) survived_events: BTreeMap<String, u32>,
+}
+
+impl ObjectiveProgress {
+    /// Returns whether the campaign was started, meaning an objective was activated at some point
+    pub(crate) fn is_started(&self) -> bool {
+        !self.active.is_empty() || !self.completed.is_empty()
+    }
+}
+
+impl ObjectiveCatalog {
+    /// Loads the objective catalog at the given path.
+    /// # Arguments
+    /// * `path` - The path of the objective catalog file
+    /// * `items` - The item registry, used to validate item ids
+    /// # Returns
+    /// * `RLResult<ObjectiveCatalog>` - The validated catalog or an error describing the invalid entry
+    pub(crate) fn load(path: &str, items: &ItemRegistry) -> RLResult<Self> {
+        info!("Loading objective catalog from {}", path);
+        let source = fs::read_to_string(path).map_err(|e| create_data_error(path, "file", e))?;
+        Self::parse(path, &source, items)
+    }
+
+    /// Parses and validates the content of an objective catalog.
+    /// # Arguments
+    /// * `file` - The name of the file the content was read from, used for error messages
+    /// * `source` - The content of the objective catalog
+    /// * `items` - The item registry, used to validate item ids
+    /// # Returns
+    /// * `RLResult<ObjectiveCatalog>` - The validated catalog or an error describing the invalid entry
+    pub(crate) fn parse(file: &str, source: &str, items: &ItemRegistry) -> RLResult<Self> {
+        let catalog: ObjectiveCatalog =
+            serde_yaml::from_str(source).map_err(|e| create_data_error(file, "objectives", e))?;
+        if catalog.get(&catalog.start).is_none() {
+            return Err(create_data_error(
+                file,
+                "start",
+                format!("unknown objective \"{}\"", catalog.start),
+            ));
+        }
+        for (i, objective) in catalog.objectives.iter().enumerate() {
+            let field = format!("objectives[{i}]");
+            if catalog.objectives[..i]
+                .iter()
+                .any(|other| other.id == objective.id)
+            {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.id"),
+                    format!("the id \"{}\" is used more than once", objective.id),
+                ));
+            }
+            if let Some(next) = objective.next.iter().find(|id| catalog.get(id).is_none()) {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.next"),
+                    format!("unknown objective \"{next}\""),
+                ));
+            }
+            let item_ids = objective
+                .conditions
+                .items_owned
+                .keys()
+                .map(|id| (id, "conditions.items_owned"))
+                .chain(
+                    objective
+                        .rewards
+                        .items
+                        .keys()
+                        .map(|id| (id, "rewards.items")),
+                );
+            for (id, items_field) in item_ids {
+                if items.get(id).is_none() {
+                    return Err(create_data_error(
+                        file,
+                        &format!("{field}.{items_field}.{id}"),
+                        "unknown item",
+                    ));
+                }
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Returns the objective with the given id
+    pub fn get(&self, id: &str) -> Option<&Objective> {
+        self.objectives.iter().find(|objective| objective.id == id)
+    }
+}
+
+impl Conditions {
+    /// Checks whether all conditions are fulfilled
+    /// # Arguments
+    /// * `activated_at` - The time (in ticks) the objective was activated
+    /// * `player` - The player, used for the items and the time
+    /// * `machines` - All machines of the game
+    /// * `progress` - The progress containing the finished trades and ended events
+    /// # Returns
+    /// * `true` if all conditions are fulfilled
+    pub(crate) fn are_fulfilled(
+        &self,
+        activated_at: u32,
+        player: &Player,
+        machines: &[Machine],
+        progress: &ObjectiveProgress,
+    ) -> bool {
+        let machine_in_state = |name: &String, check: &dyn Fn(&State) -> bool| {
+            machines
+                .iter()
+                .any(|machine| machine.name == *name && check(&machine.state))
+        };
+        let happened = |log: &BTreeMap<String, u32>, name: &Option<String>| {
+            name.as_ref().map_or(true, |name| {
+                log.get(name).map_or(false, |time| *time >= activated_at)
+            })
+        };
+        self.machines_repaired
+            .iter()
+            .all(|name| machine_in_state(name, &|state| *state != State::Broken))
+            && self
+                .machines_running
+                .iter()
+                .all(|name| machine_in_state(name, &|state| *state == State::Running))
+            && self
+                .items_owned
+                .iter()
+                .all(|(id, amount)| player.get_item_amount(id) >= *amount)
+            && player.time.saturating_sub(activated_at) >= self.time_survived * DESIRED_FPS
+            && happened(&progress.survived_events, &self.event_survived)
+            && happened(&progress.completed_trades, &self.trade_completed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::error::RLError;
+
+    const CATALOG: &str = include_str!("../../../assets/data/objectives.yaml");
+
+    fn items() -> ItemRegistry {
+        ItemRegistry::parse(
+            "items.yaml",
+            include_str!("../../../assets/data/items.yaml"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_objective_catalog() {
+        let catalog = ObjectiveCatalog::parse("objectives.yaml", CATALOG, &items()).unwrap();
+        assert_eq!(catalog.start, "landing");
+        assert_eq!(catalog.get("landing").unwrap().next, vec!["life_support"]);
+        assert!(catalog.get("rescue").unwrap().rewards.win);
+    }
+
+    #[test]
+    fn test_unknown_next_objective() {
+        let source = "start: a
+objectives:
+  - id: a
+    next: [b]
+";
+        assert!(matches!(
+            ObjectiveCatalog::parse("test.yaml", source, &items()),
+            Err(RLError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let conditions = Conditions {
+            time_survived: 1,
+            trade_completed: Some("test_trade".to_string()),
+            ..Default::default()
+        };
+        let mut player = Player::default();
+        let mut progress = ObjectiveProgress::default();
+        player.time = 60 + DESIRED_FPS;
+        progress
+            .completed_trades
+            .insert("test_trade".to_string(), 50);
+        assert!(!conditions.are_fulfilled(60, &player, &[], &progress));
+        progress
+            .completed_trades
+            .insert("test_trade".to_string(), 70);
+        assert!(conditions.are_fulfilled(60, &player, &[], &progress));
+        player.time = 61;
+        assert!(!conditions.are_fulfilled(60, &player, &[], &progress));
+    }
+}
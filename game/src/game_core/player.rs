@@ -1,14 +1,12 @@
 //! Contains everything related to the Player
 //! Author: ["Marion Hinkel", "Sander Stella", "Philipp Wolf"]
 
-use crate::backend::constants::DESIRED_FPS;
+use crate::backend::constants::{DESIRED_FPS, MAX_CARRY_WEIGHT};
 use crate::backend::rlcolor::RLColor;
 use crate::backend::screen::{Popup, StackCommand};
-use crate::game_core::item::Item;
+use crate::game_core::item::{Item, ItemRegistry};
 use crate::game_core::resources::Resources;
-use crate::languages::german::GAME_INFO;
-use crate::languages::german::{BENZIN, GEDRUCKTESTEIL, SUPER_GLUE};
-use crate::RLResult;
+use crate::{tr, RLResult};
 use serde::{Deserialize, Serialize};
 use std::sync::mpsc::Sender;
 use tracing::info;
@@ -17,30 +15,27 @@ use tracing::info;
 /// along with their change rate
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Player {
-    /// The current items of the player.
-    pub(crate) inventory: Vec<(Item, i32)>,
-    pub(crate) position: (usize, usize),
+    /// The current items of the player as item id and amount.
+    pub inventory: Vec<(String, i32)>,
+    pub position: (usize, usize),
     /// The current air, energy and life of the player.
-    pub(crate) resources: Resources<u16>,
+    pub resources: Resources<u16>,
     /// The current change rate of the air, energy and life of the player.
-    pub(crate) resources_change: Resources<i16>,
+    pub resources_change: Resources<i16>,
     /// The current milestone the player has reached.
     pub milestone: usize,
     /// helper variable to check if the player lost life in the last tick
     pub(crate) last_damage: u32,
     /// contains the current ingame time
-    pub(crate) time: u32,
+    pub time: u32,
 }
 impl Default for Player {
     fn default() -> Self {
         info!("Default Player created");
         Self {
-            inventory: vec![
-                (Item::new(SUPER_GLUE), 0),
-                (Item::new(BENZIN), 3),
-                (Item::new(GEDRUCKTESTEIL), 1),
-            ],
-            position: (600, 500),
+            inventory: vec![],
+            // Set to the spawn point of the level when a new game starts
+            position: (0, 0),
             resources: Resources {
                 oxygen: u16::MAX,
                 energy: u16::MAX,
@@ -86,7 +81,7 @@ impl Player {
             (0, last_damage, _) if last_damage >= 8 * DESIRED_FPS => {
                 self.resources_change.life += 5;
                 self.last_damage = 0;
-                let popup = Popup::new(RLColor::GREEN, GAME_INFO[0].to_string(), 5);
+                let popup = Popup::new(RLColor::GREEN, tr!("hud.life_regeneration"), 5);
                 info!("Player startet healing");
                 sender.send(StackCommand::Popup(popup))?;
             }
@@ -98,49 +93,53 @@ impl Player {
         Ok(())
     }
     /// changes the amount of an specific item in the inventory by a given number
+    /// The amount can not exceed the stack limit of the item or drop below zero
     /// # Arguments
     /// * `item` - The item to change the amount of
     /// * `amount_change` - The amount to change the item by
-    pub fn add_item(&mut self, item: &Item, amount_change: i32) {
-        self.inventory.iter_mut().for_each(|(i, amount)| {
-            if i.name == item.name {
-                *amount += amount_change;
-            }
-        });
+    /// # Returns
+    /// * `i32` - The amount which did not fit into the stack, so the caller can put it elsewhere
+    pub fn add_item(&mut self, item: &Item, amount_change: i32) -> i32 {
+        let wanted = self.get_item_amount(&item.id) + amount_change;
+        let new_amount = wanted.clamp(0, item.stack_limit);
+        match self.inventory.iter_mut().find(|(id, _)| *id == item.id) {
+            Some((_, amount)) => *amount = new_amount,
+            None => self.inventory.push((item.id.clone(), new_amount)),
+        }
+        (wanted - item.stack_limit).max(0)
+    }
+
+    /// Returns how many units of an item the player can still carry, limited by the stack limit
+    /// of the item and by the weight the player can carry
+    /// # Arguments
+    /// * `item` - The item to carry
+    /// * `items` - The item registry, used to weigh the inventory
+    pub(crate) fn room_for(&self, item: &Item, items: &ItemRegistry) -> i32 {
+        let stack = item.stack_limit - self.get_item_amount(&item.id);
+        let weight = MAX_CARRY_WEIGHT - items.weight(&self.inventory);
+        match weight.checked_div(item.weight) {
+            Some(units) => stack.min(units),
+            None => stack,
+        }
+        .max(0)
     }
     /// returns the amount of an specific item in the inventory
     /// # Arguments
-    /// * `item` - The item to get the amount of
+    /// * `item` - The id of the item to get the amount of
     /// # Returns
-    /// `ret` - The amount of the chosen item in the inventory or if the item is not in the
-    /// inventory -100
-    pub fn get_item_amount(&self, item: &Item) -> i32 {
-        let mut ret: i32 = -100;
-        self.inventory.iter().for_each(|(i, amount)| {
-            if i.name == item.name {
-                ret = *amount;
-            }
-        });
-        ret
+    /// The amount of the chosen item in the inventory, 0 if the player has none of it
+    pub fn get_item_amount(&self, item: &str) -> i32 {
+        self.inventory
+            .iter()
+            .find(|(id, _)| id == item)
+            .map_or(0, |(_, amount)| *amount)
     }
 }
 
-/// Returns a Inventory with set sizes for all items
-/// # Arguments
-/// * `super_glue` - The amount of super glue
-/// * `benzin` - The amount of benzin
-/// * `gedrucktesteil` - The amount of the printed part
-pub fn gen_inventory(super_glue: i32, benzin: i32, gedrucktesteil: i32) -> Vec<(Item, i32)> {
-    vec![
-        (Item::new(SUPER_GLUE), super_glue),
-        (Item::new(BENZIN), benzin),
-        (Item::new(GEDRUCKTESTEIL), gedrucktesteil),
-    ]
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::backend::game_data::GameData;
     use crate::backend::gamestate::GameState;
     use crate::backend::screen::Screen;
     use std::sync::mpsc::{channel, Receiver};
@@ -225,4 +224,45 @@ mod test {
         assert_eq!(player.resources_change.life, -1);
         assert_eq!(player.last_damage, 0);
     }
+
+    #[test]
+    fn test_add_item_stack_limit() {
+        let item = Item {
+            id: "super_glue".to_string(),
+            name: "SuperGlue".to_string(),
+            info_text: String::new(),
+            img: "SuperGlue.png".to_string(),
+            stack_limit: 5,
+            weight: 1,
+            start_amount: 0,
+        };
+        let mut player = Player::default();
+        assert_eq!(player.get_item_amount("super_glue"), 0);
+        assert_eq!(player.add_item(&item, 3), 0);
+        // The amount above the stack limit is returned
+        assert_eq!(player.add_item(&item, 3), 1);
+        assert_eq!(player.get_item_amount("super_glue"), 5);
+        assert_eq!(player.add_item(&item, -2), 0);
+        assert_eq!(player.get_item_amount("super_glue"), 3);
+        // The amount never drops below zero
+        player.add_item(&item, -5);
+        assert_eq!(player.get_item_amount("super_glue"), 0);
+    }
+
+    #[test]
+    fn test_room_for_weight() {
+        let items = GameData::bundled().item_registry;
+        let benzin = items.get("benzin").unwrap();
+        let glue = items.get("super_glue").unwrap();
+        let mut player = Player::default();
+        assert_eq!(player.room_for(benzin, &items), benzin.stack_limit);
+        // Once the player carries a lot of glue, the weight limits the benzin
+        player.add_item(glue, glue.stack_limit);
+        let free = MAX_CARRY_WEIGHT - glue.stack_limit * glue.weight;
+        assert_eq!(
+            player.room_for(benzin, &items),
+            benzin.stack_limit.min(free / benzin.weight)
+        );
+        assert_eq!(player.room_for(glue, &items), 0);
+    }
 }
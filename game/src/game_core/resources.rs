@@ -11,9 +11,9 @@ use std::ops;
 /// Use type `u16` for the resource amount
 #[derive(Copy, Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Resources<T: PartialOrd> {
-    pub(crate) oxygen: T,
-    pub(crate) energy: T,
-    pub(crate) life: T,
+    pub oxygen: T,
+    pub energy: T,
+    pub life: T,
 }
 
 /// Define how struct will be created from an iterator
@@ -0,0 +1,211 @@
+//! Contains the locale system, which loads the string tables of a language and translates message ids.
+use crate::backend::error::create_data_error;
+use crate::RLResult;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use tracing::info;
+
+thread_local! {
+    /// The locale which is used by `translate` and the `tr!` macro
+    static LOCALE: RefCell<Locale> = RefCell::new(Locale::default());
+}
+
+/// A single entry of a string table
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Message {
+    /// A text which is the same for every count
+    Text(String),
+    /// A text which depends on the `count` placeholder
+    Plural {
+        /// Used if the count is exactly one
+        one: String,
+        /// Used for every other count
+        other: String,
+    },
+}
+
+/// The string table of a language
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Locale {
+    /// The name of the language shown in the language selector
+    pub(crate) language_name: String,
+    /// Maps message ids to their text
+    messages: BTreeMap<String, Message>,
+}
+
+impl Locale {
+    /// Loads the string table of the given language from `assets/lang`.
+    /// # Arguments
+    /// * `language` - The language code, e.g. `de` or `en`
+    /// # Returns
+    /// * `RLResult<Locale>` - The validated locale or an error describing the invalid entry
+    pub(crate) fn load(language: &str) -> RLResult<Self> {
+        let path = format!("assets/lang/{language}.yaml");
+        info!("Loading locale from {}", path);
+        let source = fs::read_to_string(&path).map_err(|e| create_data_error(&path, "file", e))?;
+        Self::parse(&path, &source)
+    }
+
+    /// Parses and validates the content of a string table.
+    /// # Arguments
+    /// * `file` - The name of the file the content was read from, used for error messages
+    /// * `source` - The content of the string table
+    /// # Returns
+    /// * `RLResult<Locale>` - The validated locale or an error describing the invalid entry
+    pub(crate) fn parse(file: &str, source: &str) -> RLResult<Self> {
+        let locale: Locale =
+            serde_yaml::from_str(source).map_err(|e| create_data_error(file, "messages", e))?;
+        if let Some(id) = locale
+            .messages
+            .keys()
+            .find(|id| id.is_empty() || id.contains(char::is_whitespace))
+        {
+            return Err(create_data_error(
+                file,
+                &format!("messages.{id}"),
+                "the message id must not be empty or contain whitespace",
+            ));
+        }
+        Ok(locale)
+    }
+
+    /// Translates a message id and replaces all placeholders.
+    /// Unknown message ids are returned unchanged, so that plain text can be used instead of an id.
+    /// # Arguments
+    /// * `id` - The message id
+    /// * `args` - The names and values of the placeholders, a `count` argument selects the plural form
+    /// # Returns
+    /// * `String` - The translated message
+    pub(crate) fn translate(&self, id: &str, args: &[(&str, String)]) -> String {
+        let text = match self.messages.get(id) {
+            Some(Message::Text(text)) => text,
+            Some(Message::Plural { one, other }) => {
+                let count = args
+                    .iter()
+                    .find(|(name, _)| *name == "count")
+                    .map(|(_, value)| value.as_str());
+                if count == Some("1") {
+                    one
+                } else {
+                    other
+                }
+            }
+            None => id,
+        };
+        args.iter().fold(text.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+    }
+}
+
+/// Loads the given language and uses it for all following translations.
+/// # Arguments
+/// * `language` - The language code, e.g. `de` or `en`
+/// # Returns
+/// * `RLResult` - Fails if the string table of the language could not be loaded
+pub fn set_language(language: &str) -> RLResult {
+    let locale = Locale::load(language)?;
+    info!("Language changed to {}", locale.language_name);
+    LOCALE.with(|current| *current.borrow_mut() = locale);
+    Ok(())
+}
+
+/// Translates a message id with the current locale, use the `tr!` macro instead of calling this directly.
+/// # Arguments
+/// * `id` - The message id
+/// * `args` - The names and values of the placeholders
+/// # Returns
+/// * `String` - The translated message
+pub(crate) fn translate(id: &str, args: &[(&str, String)]) -> String {
+    LOCALE.with(|locale| locale.borrow().translate(id, args))
+}
+
+/// Uses the English string table of the repository on the current thread, so that tests do not
+/// depend on the working directory or on the locale other tests left behind
+#[cfg(test)]
+pub(crate) fn use_english() {
+    let source = include_str!("../../../assets/lang/en.yaml");
+    let locale = Locale::parse("en.yaml", source).unwrap();
+    LOCALE.with(|current| *current.borrow_mut() = locale);
+}
+
+/// Returns the name of the current language
+pub(crate) fn language_name() -> String {
+    LOCALE.with(|locale| locale.borrow().language_name.clone())
+}
+
+/// Macro for translating a message id with the current locale.
+/// Placeholders are passed as `name = value`, a `count` placeholder selects the plural form.
+#[macro_export]
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::languages::locale::translate($id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::languages::locale::translate($id, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::error::RLError;
+    use crate::backend::game_data::GameData;
+
+    const GERMAN: &str = include_str!("../../../assets/lang/de.yaml");
+    const ENGLISH: &str = include_str!("../../../assets/lang/en.yaml");
+
+    #[test]
+    fn test_placeholders_and_plurals() {
+        let locale = Locale::parse("en.yaml", ENGLISH).unwrap();
+        assert_eq!(
+            locale.translate("trade.missing_items", &[("count", 1.to_string())]),
+            "The following item is missing to execute the trade:"
+        );
+        assert_eq!(
+            locale.translate("trade.missing_items", &[("count", 2.to_string())]),
+            "The following items are missing to execute the trade:"
+        );
+        assert_eq!(
+            locale.translate(
+                "trade.missing_item",
+                &[("count", 3.to_string()), ("item", "Petrol".to_string())]
+            ),
+            "*3 Petrol"
+        );
+        assert_eq!(locale.translate("unknown.message", &[]), "unknown.message");
+    }
+
+    #[test]
+    fn test_locales_are_complete() {
+        let german = Locale::parse("de.yaml", GERMAN).unwrap();
+        let english = Locale::parse("en.yaml", ENGLISH).unwrap();
+        assert!(german.messages.keys().eq(english.messages.keys()));
+    }
+
+    #[test]
+    fn test_event_texts_are_translated() {
+        let english = Locale::parse("en.yaml", ENGLISH).unwrap();
+        for event in GameData::bundled().event_catalog.events {
+            for id in event.messages.iter().chain([&event.info_text]) {
+                assert!(english.messages.contains_key(id), "{} is missing", id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_message_id() {
+        let source = "language_name: Test
+messages:
+  \"invalid id\": Test
+";
+        assert!(matches!(
+            Locale::parse("test.yaml", source),
+            Err(RLError::InvalidData(_))
+        ));
+    }
+}
@@ -1 +1 @@
-pub(crate) mod german;
+pub mod locale;
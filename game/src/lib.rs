@@ -0,0 +1,17 @@
+//! The modules of Red Life, shared by the game and the `rl-save` tool
+#![warn(clippy::pedantic)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_possible_truncation)]
+// The library only shares the modules of the game with its binaries, it is no public API
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::must_use_candidate)]
+pub mod backend;
+pub mod bots;
+pub mod game_core;
+pub mod languages;
+pub mod machines;
+mod main_menu;
+
+/// Our own Result Type for custom Error handling.
+pub type RLResult<T = ()> = Result<T, backend::error::RLError>;
@@ -0,0 +1,420 @@
+//! Contains the machine catalog, which describes all machines of the game in a data file.
+use crate::backend::error::create_data_error;
+use crate::game_core::item::ItemRegistry;
+use crate::game_core::resources::Resources;
+use crate::machines::machine::{default_output_capacity, Machine, State};
+use crate::machines::recipe::Recipe;
+use crate::machines::trade::{default_refund, Trade};
+use crate::RLResult;
+use ggez::graphics::Rect;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use tracing::info;
+
+/// The content of a machine catalog file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MachineCatalog {
+    machines: Vec<MachineDefinition>,
+}
+
+/// Describes a single machine as it is written in the machine catalog
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MachineDefinition {
+    /// The name of the machine, also used to find its sprites and to place it in a level
+    name: String,
+    /// The size of the area the player is prevented from walking into
+    size: Size,
+    /// All trades the player can do with this machine
+    trades: Vec<TradeDefinition>,
+    /// All recipes the machine can produce
+    #[serde(default)]
+    recipes: Vec<RecipeDefinition>,
+    /// Resources consumed and or produced while the machine is running
+    #[serde(default)]
+    running_resources: Resources<i16>,
+    /// Durability lost per second while running, machines without wear never break down by themselves
+    #[serde(default)]
+    wear: u16,
+    /// The amount of items the output buffer holds until the player collects them
+    #[serde(default = "default_output_capacity")]
+    output_capacity: u16,
+}
+
+/// The size of a machine as it is written in the machine catalog
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Size {
+    w: f32,
+    h: f32,
+}
+
+/// Describes a single trade of a machine as it is written in the machine catalog
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TradeDefinition {
+    name: String,
+    #[serde(default)]
+    time_ticks: i16,
+    initial_state: State,
+    resulting_state: State,
+    #[serde(default)]
+    return_after_timer: bool,
+    /// Maps item ids to their amount, negative amounts are given to the player
+    #[serde(default)]
+    cost: BTreeMap<String, i32>,
+    /// Whether the machine is as good as new once the trade is finished
+    #[serde(default)]
+    restores_durability: bool,
+    /// The percentage of the paid items the player gets back if the trade is cancelled
+    #[serde(default = "default_refund")]
+    refund: u8,
+}
+
+/// Describes a single recipe of a machine as it is written in the machine catalog
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecipeDefinition {
+    name: String,
+    time_ticks: i16,
+    /// Maps item ids to the amount the player pays for one unit
+    #[serde(default)]
+    inputs: BTreeMap<String, i32>,
+    /// Maps item ids to the amount the player gets for one unit
+    outputs: BTreeMap<String, i32>,
+}
+
+/// Loads all machines from the machine catalog at the given path.
+/// # Arguments
+/// * `path` - The path of the machine catalog file
+/// * `items` - The item registry, used to validate the trade costs
+/// # Returns
+/// * `RLResult<Vec<Machine>>` - The machines of the catalog, which still need to be placed by a level,
+///   or an error describing the invalid entry
+pub(crate) fn load_machine_catalog(path: &str, items: &ItemRegistry) -> RLResult<Vec<Machine>> {
+    info!("Loading machine catalog from {}", path);
+    let source = fs::read_to_string(path).map_err(|e| create_data_error(path, "file", e))?;
+    parse_machine_catalog(path, &source, items)
+}
+
+/// Parses and validates the content of a machine catalog.
+/// # Arguments
+/// * `file` - The name of the file the content was read from, used for error messages
+/// * `source` - The content of the machine catalog
+/// * `items` - The item registry, used to validate the trade costs
+/// # Returns
+/// * `RLResult<Vec<Machine>>` - The machines of the catalog or an error describing the invalid entry
+pub(crate) fn parse_machine_catalog(
+    file: &str,
+    source: &str,
+    items: &ItemRegistry,
+) -> RLResult<Vec<Machine>> {
+    let catalog: MachineCatalog =
+        serde_yaml::from_str(source).map_err(|e| create_data_error(file, "machines", e))?;
+    if catalog.machines.is_empty() {
+        return Err(create_data_error(
+            file,
+            "machines",
+            "the catalog does not contain any machine",
+        ));
+    }
+    for (i, machine) in catalog.machines.iter().enumerate() {
+        if catalog.machines[..i]
+            .iter()
+            .any(|other| other.name == machine.name)
+        {
+            return Err(create_data_error(
+                file,
+                &format!("machines[{i}].name"),
+                format!("the name \"{}\" is used more than once", machine.name),
+            ));
+        }
+    }
+    catalog
+        .machines
+        .into_iter()
+        .enumerate()
+        .map(|(i, machine)| machine.into_machine(file, &format!("machines[{i}]"), items))
+        .collect()
+}
+
+impl MachineDefinition {
+    /// Validates the definition and converts it into a `Machine`
+    /// # Arguments
+    /// * `file` - The catalog file, used for error messages
+    /// * `field` - The path of this definition inside the catalog, used for error messages
+    /// * `items` - The item registry, used to validate the trade costs
+    fn into_machine(self, file: &str, field: &str, items: &ItemRegistry) -> RLResult<Machine> {
+        if self.name.trim().is_empty() {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.name"),
+                "the name must not be empty",
+            ));
+        }
+        if self.size.w <= 0. || self.size.h <= 0. {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.size"),
+                "the size must be greater than zero",
+            ));
+        }
+        if self.trades.is_empty() {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.trades"),
+                "a machine needs at least one trade",
+            ));
+        }
+        let trades = self
+            .trades
+            .into_iter()
+            .enumerate()
+            .map(|(i, trade)| trade.into_trade(file, &format!("{field}.trades[{i}]"), items))
+            .collect::<RLResult<Vec<Trade>>>()?;
+        let recipes = self
+            .recipes
+            .into_iter()
+            .enumerate()
+            .map(|(i, recipe)| recipe.into_recipe(file, &format!("{field}.recipes[{i}]"), items))
+            .collect::<RLResult<Vec<Recipe>>>()?;
+        if let Some(recipe) = recipes
+            .iter()
+            .find(|recipe| recipe.output_units() > i32::from(self.output_capacity))
+        {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.output_capacity"),
+                format!("the output buffer can not hold a unit of {}", recipe.name),
+            ));
+        }
+        Ok(Machine::new_by_const((
+            self.name,
+            Rect::new(0., 0., self.size.w, self.size.h),
+            trades,
+            recipes,
+            self.running_resources,
+            self.wear,
+            self.output_capacity,
+        )))
+    }
+}
+
+impl TradeDefinition {
+    /// Validates the definition and converts it into a `Trade`
+    /// # Arguments
+    /// * `file` - The catalog file, used for error messages
+    /// * `field` - The path of this definition inside the catalog, used for error messages
+    /// * `items` - The item registry, used to validate the trade costs
+    fn into_trade(self, file: &str, field: &str, items: &ItemRegistry) -> RLResult<Trade> {
+        if self.name.trim().is_empty() {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.name"),
+                "the name must not be empty",
+            ));
+        }
+        if self.time_ticks < 0 {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.time_ticks"),
+                "the duration must not be negative",
+            ));
+        }
+        if let Some(id) = self.cost.keys().find(|id| items.get(id).is_none()) {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.cost.{id}"),
+                "unknown item",
+            ));
+        }
+        if self.refund > 100 {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.refund"),
+                "the refund must not be more than 100 percent",
+            ));
+        }
+        let cost = self.cost.into_iter().collect();
+        Ok(Trade {
+            refund: self.refund,
+            ..Trade::new(
+                self.name,
+                self.time_ticks,
+                self.initial_state,
+                self.resulting_state,
+                self.return_after_timer,
+                cost,
+                self.restores_durability,
+            )
+        })
+    }
+}
+
+impl RecipeDefinition {
+    /// Validates the definition and converts it into a `Recipe`
+    /// # Arguments
+    /// * `file` - The catalog file, used for error messages
+    /// * `field` - The path of this definition inside the catalog, used for error messages
+    /// * `items` - The item registry, used to validate the inputs and outputs
+    fn into_recipe(self, file: &str, field: &str, items: &ItemRegistry) -> RLResult<Recipe> {
+        if self.name.trim().is_empty() {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.name"),
+                "the name must not be empty",
+            ));
+        }
+        if self.time_ticks <= 0 {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.time_ticks"),
+                "the duration must be greater than zero",
+            ));
+        }
+        if self.outputs.is_empty() {
+            return Err(create_data_error(
+                file,
+                &format!("{field}.outputs"),
+                "a recipe needs at least one output",
+            ));
+        }
+        for (list, entries) in [("inputs", &self.inputs), ("outputs", &self.outputs)] {
+            if let Some(id) = entries.keys().find(|id| items.get(id).is_none()) {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.{list}.{id}"),
+                    "unknown item",
+                ));
+            }
+            if let Some((id, _)) = entries.iter().find(|(_, amount)| **amount <= 0) {
+                return Err(create_data_error(
+                    file,
+                    &format!("{field}.{list}.{id}"),
+                    "the amount must be greater than zero",
+                ));
+            }
+        }
+        Ok(Recipe::new(
+            self.name,
+            self.inputs.into_iter().collect(),
+            self.outputs.into_iter().collect(),
+            self.time_ticks,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::error::RLError;
+
+    const CATALOG: &str = include_str!("../../../assets/data/machines.yaml");
+
+    fn items() -> ItemRegistry {
+        ItemRegistry::parse(
+            "items.yaml",
+            include_str!("../../../assets/data/items.yaml"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_machine_catalog() {
+        let machines = parse_machine_catalog("machines.yaml", CATALOG, &items()).unwrap();
+        assert_eq!(machines.len(), 6);
+        assert_eq!(machines[0].name, "Sauerstoffgenerator");
+        assert_eq!(
+            machines[2].recipes[0].outputs,
+            vec![("super_glue".to_string(), 1)]
+        );
+        assert!(machines[2].recipes[0].inputs.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_item() {
+        let source = "machines:
+  - name: Test
+    size: { w: 1.0, h: 1.0 }
+    trades:
+      - name: test_trade
+        initial_state: Broken
+        resulting_state: Idle
+        cost: { gold: 1 }
+";
+        match parse_machine_catalog("test.yaml", source, &items()) {
+            Err(RLError::InvalidData(message)) => {
+                assert_eq!(
+                    message,
+                    "test.yaml: machines[0].trades[0].cost.gold: unknown item"
+                );
+            }
+            other => panic!("Expected an InvalidData error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_size() {
+        let source = "machines:
+  - name: Test
+    size: { w: 0.0, h: 1.0 }
+    trades:
+      - name: test_trade
+        initial_state: Broken
+        resulting_state: Idle
+";
+        assert!(matches!(
+            parse_machine_catalog("test.yaml", source, &items()),
+            Err(RLError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_recipe_amount() {
+        let source = "machines:
+  - name: Test
+    size: { w: 1.0, h: 1.0 }
+    trades:
+      - name: test_trade
+        initial_state: Broken
+        resulting_state: Idle
+    recipes:
+      - name: test_recipe
+        time_ticks: 10
+        outputs: { super_glue: -1 }
+";
+        match parse_machine_catalog("test.yaml", source, &items()) {
+            Err(RLError::InvalidData(message)) => {
+                assert_eq!(
+                    message,
+                    "test.yaml: machines[0].recipes[0].outputs.super_glue: the amount must be greater than zero"
+                );
+            }
+            other => panic!("Expected an InvalidData error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_refund() {
+        let source = "machines:
+  - name: Test
+    size: { w: 1.0, h: 1.0 }
+    trades:
+      - name: test_trade
+        initial_state: Broken
+        resulting_state: Idle
+        refund: 150
+";
+        match parse_machine_catalog("test.yaml", source, &items()) {
+            Err(RLError::InvalidData(message)) => {
+                assert_eq!(
+                    message,
+                    "test.yaml: machines[0].trades[0].refund: the refund must not be more than 100 percent"
+                );
+            }
+            other => panic!("Expected an InvalidData error, got {other:?}"),
+        }
+    }
+}
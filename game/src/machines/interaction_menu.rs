@@ -0,0 +1,317 @@
+//! Contains the interaction menu, which lets the player choose between the actions of a machine
+//! if it offers more than one in its current state.
+use crate::backend::constants::{DESIRED_FPS, MENU_AREA};
+use crate::backend::gamestate::GameState;
+use crate::backend::rlcolor::RLColor;
+use crate::backend::utils::get_draw_params;
+use crate::game_core::item::ItemRegistry;
+use crate::machines::machine::MachineOption;
+use crate::{draw, tr, RLResult};
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, DrawMode, Mesh, Rect, Text, TextFragment};
+use ggez::Context;
+use tracing::info;
+
+/// Lists items with their amounts and names, a dash stands for no items
+/// # Arguments
+/// * `items` - The ids and amounts of the items
+/// * `registry` - The item registry, used to show the names of the items
+fn list_items(items: &[(String, i32)], registry: &ItemRegistry) -> String {
+    if items.is_empty() {
+        return "-".to_string();
+    }
+    items
+        .iter()
+        .map(|(item, amount)| format!("{amount} {}", registry.name(item)))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Returns the area of a row of the menu, the first row is the title
+/// # Arguments
+/// * `row` - The index of the row
+fn row_area(row: usize) -> Rect {
+    let (x, y, w, h) = MENU_AREA;
+    Rect::new(x, y + 20. + h * row as f32, w, h)
+}
+
+impl GameState {
+    /// Interacts with the machine next to the player. A halted production is resumed first, without
+    /// opening the menu. A machine with more than one option opens the interaction menu, otherwise
+    /// the only option is chosen right away. Cancelling a trade is never chosen right away, so that
+    /// the player does not cancel it by accident.
+    /// Without a machine next to the player the nearest door is opened or sealed.
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the interaction
+    pub(crate) fn interact(&mut self) -> RLResult {
+        let position = self.player.position;
+        let Some(index) = self
+            .machines
+            .iter()
+            .position(|machine| machine.is_interactable(position))
+        else {
+            self.toggle_door();
+            return Ok(());
+        };
+        info!("Interacting with machine: {}", self.machines[index].name);
+        let options = self.machines[index].options();
+        let halted = self.machines[index].is_halted();
+        if !halted && (options.len() > 1 || options.contains(&MachineOption::Cancel)) {
+            self.interaction_menu = Some(index);
+            return Ok(());
+        }
+        let player = &self.player.clone();
+        let items = &self.item_registry.clone();
+        self.machines[index].interact(player, items)
+    }
+
+    /// Chooses an option of the open interaction menu and closes it
+    /// # Arguments
+    /// * `index` - The place of the option in the menu, starting at 0
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the interaction
+    pub(crate) fn choose_option(&mut self, index: usize) -> RLResult {
+        let Some(machine) = self.interaction_menu else {
+            return Ok(());
+        };
+        let Some(option) = self.machines[machine].options().get(index).copied() else {
+            return Ok(());
+        };
+        self.interaction_menu = None;
+        let player = &self.player.clone();
+        let items = &self.item_registry.clone();
+        self.machines[machine].choose(option, player, items)
+    }
+
+    /// Closes the interaction menu once the player walked away from its machine
+    pub(crate) fn check_interaction_menu(&mut self) {
+        if let Some(machine) = self.interaction_menu {
+            if !self.machines[machine].is_interactable(self.player.position) {
+                self.interaction_menu = None;
+            }
+        }
+    }
+
+    /// Returns the option of the open interaction menu at a position
+    /// # Arguments
+    /// * `position` - The position on the unscaled screen, e.g. of the mouse
+    /// # Returns
+    /// * `Option<usize>` - The place of the option in the menu, `None` if there is none
+    pub(crate) fn menu_option_at(&self, position: (f32, f32)) -> Option<usize> {
+        let machine = self.interaction_menu?;
+        (0..self.machines[machine].options().len())
+            .find(|i| row_area(i + 1).contains(Vec2::from(position)))
+    }
+
+    /// Draws the interaction menu with the cost, the output and the duration of every option.
+    /// Options the player can afford are green, the others red.
+    /// # Arguments
+    /// * `canvas`: The canvas to draw on
+    /// * `scale`: The scale of the canvas
+    /// * `ctx`: The `Context` of the game
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the paint function
+    pub(crate) fn draw_interaction_menu(
+        &self,
+        canvas: &mut Canvas,
+        scale: Vec2,
+        ctx: &mut Context,
+    ) -> RLResult {
+        let Some(index) = self.interaction_menu else {
+            return Ok(());
+        };
+        let machine = &self.machines[index];
+        let mut lines = vec![(machine.name.clone(), RLColor::GOLD)];
+        for (i, option) in machine.options().into_iter().enumerate() {
+            let Some(offer) = machine.describe(option) else {
+                continue;
+            };
+            let color = if machine.is_affordable(option, &self.player) {
+                RLColor::STATUS_GREEN
+            } else {
+                RLColor::STATUS_RED
+            };
+            lines.push((
+                format!(
+                    "{}. {} ({:.1} s)  {}: {}  {}: {}",
+                    i + 1,
+                    tr!(&format!("action.{}", offer.name)),
+                    f32::from(offer.time_ticks) / DESIRED_FPS as f32,
+                    tr!("menu.cost"),
+                    list_items(&offer.inputs, &self.item_registry),
+                    tr!("menu.output"),
+                    list_items(&offer.outputs, &self.item_registry)
+                ),
+                color,
+            ));
+        }
+        lines.push((tr!("menu.hint"), RLColor::LIGHT_GREY));
+        let background = Mesh::new_rounded_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., MENU_AREA.2, 40. + MENU_AREA.3 * lines.len() as f32),
+            10.,
+            RLColor::SHADOW,
+        )?;
+        draw!(
+            canvas,
+            &background,
+            Vec2::new(MENU_AREA.0, MENU_AREA.1),
+            scale
+        );
+        for (i, (line, color)) in lines.into_iter().enumerate() {
+            let area = row_area(i);
+            let text = Text::new(TextFragment::new(line).color(color).scale(20.));
+            draw!(canvas, &text, Vec2::new(area.x + 20., area.y), scale);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::movement::Input;
+    use crate::machines::machine::State;
+
+    #[test]
+    fn test_options_depend_on_state() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        let index = gamestate.machine_index("Werkermaschine");
+        gamestate.stand_next_to(index);
+        let machine = &mut gamestate.machines[index];
+        machine.change_state_to(&State::Broken);
+        assert_eq!(machine.options(), vec![MachineOption::Trade(0)]);
+        machine.change_state_to(&State::Idle);
+        // The recipes come before the maintenance
+        assert_eq!(
+            machine.options(),
+            vec![
+                MachineOption::Recipe(0),
+                MachineOption::Recipe(1),
+                MachineOption::Trade(1)
+            ]
+        );
+        let offer = machine.describe(MachineOption::Trade(0)).unwrap();
+        assert_eq!(offer.inputs, vec![("printed_part".to_string(), 1)]);
+        assert!(offer.outputs.is_empty());
+    }
+
+    #[test]
+    fn test_choose_from_menu() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        let index = gamestate.machine_index("Werkermaschine");
+        gamestate.stand_next_to(index);
+        gamestate.machines[index].change_state_to(&State::Idle);
+        let part = gamestate.item_registry.get("printed_part").unwrap().clone();
+        gamestate.player.add_item(&part, 1);
+        let interact = Input {
+            interact: true,
+            ..Input::default()
+        };
+        gamestate.move_player(interact).unwrap();
+        assert_eq!(gamestate.interaction_menu, Some(index));
+        // The rows of the menu can be clicked
+        let row = row_area(2);
+        assert_eq!(gamestate.menu_option_at((row.x + 1., row.y + 1.)), Some(1));
+        assert_eq!(gamestate.menu_option_at((0., 0.)), None);
+        // Options which are not in the menu are ignored
+        gamestate.choose_option(5).unwrap();
+        assert_eq!(gamestate.interaction_menu, Some(index));
+        gamestate
+            .move_player(Input {
+                choose: Some(1),
+                ..Input::default()
+            })
+            .unwrap();
+        assert_eq!(gamestate.interaction_menu, None);
+        let recycling = gamestate.machines[index].recipes[1].name.clone();
+        assert_eq!(gamestate.machines[index].queue, vec![(recycling, 1)]);
+        // Walking away closes the menu
+        gamestate.move_player(interact).unwrap();
+        assert_eq!(gamestate.interaction_menu, Some(index));
+        gamestate.player.position = (0, 0);
+        gamestate.check_interaction_menu();
+        assert_eq!(gamestate.interaction_menu, None);
+    }
+
+    #[test]
+    fn test_resume_without_menu() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        let index = gamestate.machine_index("Werkermaschine");
+        gamestate.stand_next_to(index);
+        gamestate.machines[index].change_state_to(&State::Idle);
+        let part = gamestate.item_registry.get("printed_part").unwrap().clone();
+        gamestate.player.add_item(&part, 1);
+        for option in [0, 1] {
+            gamestate.interact().unwrap();
+            gamestate.choose_option(option).unwrap();
+        }
+        gamestate.tick().unwrap();
+        assert_eq!(gamestate.machines[index].state, State::Running);
+        gamestate.machines[index].no_energy();
+        assert!(gamestate.machines[index].is_halted());
+        // Interacting with the halted machine resumes it without opening the menu or paying anything
+        let queue = gamestate.machines[index].queue.clone();
+        let inventory = gamestate.player.inventory.clone();
+        gamestate.interact().unwrap();
+        assert_eq!(gamestate.interaction_menu, None);
+        assert!(!gamestate.machines[index].is_halted());
+        gamestate.tick().unwrap();
+        assert_eq!(gamestate.machines[index].queue, queue);
+        assert_eq!(gamestate.player.inventory, inventory);
+        assert_eq!(gamestate.machines[index].state, State::Running);
+        // The menu opens again once the production runs
+        gamestate.interact().unwrap();
+        assert_eq!(gamestate.interaction_menu, Some(index));
+    }
+
+    #[test]
+    fn test_cancel_trade() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        let index = gamestate.machine_index("Stromgenerator");
+        gamestate.stand_next_to(index);
+        gamestate.machines[index].change_state_to(&State::Broken);
+        gamestate.machines[index].trades[0].refund = 100;
+        let benzin = gamestate.item_registry.get("benzin").unwrap().clone();
+        gamestate
+            .player
+            .add_item(&benzin, 1 - gamestate.player.get_item_amount("benzin"));
+        let interact = Input {
+            interact: true,
+            ..Input::default()
+        };
+        gamestate.move_player(interact).unwrap();
+        for _ in 0..5 {
+            gamestate.tick().unwrap();
+        }
+        assert_eq!(gamestate.machines[index].state, State::Running);
+        let running = gamestate.player.resources_change;
+        assert_eq!(gamestate.player.get_item_amount("benzin"), 0);
+        // A running trade can only be cancelled and the menu opens even for this single option
+        gamestate.move_player(interact).unwrap();
+        assert_eq!(gamestate.interaction_menu, Some(index));
+        assert_eq!(
+            gamestate.machines[index].options(),
+            vec![MachineOption::Cancel]
+        );
+        gamestate
+            .move_player(Input {
+                choose: Some(0),
+                ..Input::default()
+            })
+            .unwrap();
+        for _ in 0..5 {
+            gamestate.tick().unwrap();
+        }
+        let machine = &gamestate.machines[index];
+        assert_eq!(machine.state, State::Broken);
+        assert!(!machine.is_busy());
+        // The energy of the generator is no longer added once it is broken again
+        assert_eq!(
+            running.energy - gamestate.player.resources_change.energy,
+            machine.get_energy()
+        );
+        assert_eq!(gamestate.player.get_item_amount("benzin"), 1);
+    }
+}
@@ -1,24 +1,26 @@
 //! This File handles everything about Machine
 //! Author: [ "Sander Stella", "Philipp Wolf"]
-use crate::backend::constants::PLAYER_INTERACTION_RADIUS;
+use crate::backend::constants::{
+    DEFAULT_OUTPUT_CAPACITY, DURABILITY_THRESHOLD, MAX_DURABILITY, PLAYER_INTERACTION_RADIUS,
+};
 use crate::backend::gamestate::GameCommand;
 use crate::backend::rlcolor::RLColor;
 use crate::backend::screen::{Popup, StackCommand};
 use crate::backend::utils::is_colliding;
-use crate::game_core::item::Item;
+use crate::game_core::item::ItemRegistry;
 use crate::game_core::player::Player;
 use crate::game_core::resources::Resources;
-use crate::languages::german::TRADE_CONFLICT_POPUP;
 use crate::machines::machine::State::{Broken, Idle, Running};
 use crate::machines::machine_sprite::MachineSprite;
+use crate::machines::recipe::Recipe;
 use crate::machines::trade::Trade;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::sync::mpsc::Sender;
 
-use crate::RLResult;
+use crate::{tr, RLResult};
 use ggez::graphics::{Color, Image, Rect};
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum State {
@@ -46,6 +48,17 @@ impl From<State> for Color {
         }
     }
 }
+/// An action the player can choose when interacting with a machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MachineOption {
+    /// Executes the trade at this index of the trades of the machine
+    Trade(usize),
+    /// Queues one unit of the recipe at this index of the recipes of the machine
+    Recipe(usize),
+    /// Cancels the running trade and gives back a part of its cost
+    Cancel,
+}
+
 /// The Machine Class handles any internal logic surrounding interactable objects
 /// This includes objects that arnt classic Machines per se but since they do behave so similarity
 /// we can reuse the same code for it
@@ -61,6 +74,19 @@ pub struct Machine {
     pub interaction_area: Rect,
     /// Contains a defined list of Trades, things the player can do with the Machine
     pub trades: Vec<Trade>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Contains the recipes the Machine can produce
+    pub recipes: Vec<Recipe>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Contains the queued recipes and how many units of each are left to produce,
+    /// the first one is produced next
+    pub queue: Vec<(String, u16)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Contains the ids and amounts of the finished items waiting for the player to collect them
+    pub output: Vec<(String, i32)>,
+    #[serde(default = "default_output_capacity")]
+    /// The amount of items the output buffer holds, the production pauses while it is full
+    pub(crate) output_capacity: u16,
     /// Contains the last trade with a timer, Is uses to get information about the trade after the timer runs out
     last_trade: Trade,
     /// Denotes what amount of Resources is consumed and or produced as long as the Machine is in state running
@@ -69,6 +95,23 @@ pub struct Machine {
     time_remaining: i16,
     /// Denotes weather the timer is running or not via 0 or 1 also used for calculations
     time_change: i16,
+    #[serde(default)]
+    /// Contains the amount of ticks until the unit in production is finished, 0 if none is produced
+    production_remaining: i16,
+    #[serde(default)]
+    /// Set once a brownout or a breakdown stopped the production, the next interaction resumes it
+    halted: bool,
+    #[serde(default = "full_durability")]
+    /// The condition of the machine, up to `MAX_DURABILITY`. It decreases while the machine is
+    /// running and the machine can break down once it is below `DURABILITY_THRESHOLD`.
+    durability: u16,
+    #[serde(default)]
+    /// The durability lost per second while running, 0 for machines which never wear down
+    wear: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The state the machine is set to once it is initialized, only set for newly placed machines.
+    /// It is saved so that recordings can start before the machines are initialized.
+    start_state: Option<State>,
     #[serde(skip)]
     /// Contains all the Sprites for this one Machine
     sprite: Option<MachineSprite>,
@@ -80,20 +123,36 @@ pub struct Machine {
     screen_sender: Option<Sender<StackCommand>>,
 }
 
+/// Returns the durability of a machine which is as good as new
+fn full_durability() -> u16 {
+    MAX_DURABILITY
+}
+
+/// Returns the capacity of the output buffer of machines which do not set their own
+pub(crate) fn default_output_capacity() -> u16 {
+    DEFAULT_OUTPUT_CAPACITY
+}
+
 impl Machine {
     /// Creates a new Machine with all non Optional parameters
     /// # Arguments
     /// * `name` - Name of this Machine and asset group
     /// * `hitbox` - A rect containing position and size of the Machine
     /// * `trades` - A list of Trades
+    /// * `recipes` - A list of Recipes the machine can produce
     /// * `running_resources` - Amount of recourse consumed and or produced while running
+    /// * `wear` - Durability lost per second while running
+    /// * `output_capacity` - Amount of items the output buffer holds
     /// # Returns
     /// * 'Machine'
     fn new(
         name: String,
         hitbox: Rect,
         trades: Vec<Trade>,
+        recipes: Vec<Recipe>,
         running_resources: Resources<i16>,
+        wear: u16,
+        output_capacity: u16,
     ) -> Self {
         info!("Creating new machine: name: {}", name);
         Self {
@@ -106,12 +165,21 @@ impl Machine {
                 h: hitbox.h + (PLAYER_INTERACTION_RADIUS * 2.),
             },
             state: Broken,
+            start_state: None,
             sprite: None,
             trades,
+            recipes,
+            queue: vec![],
+            output: vec![],
+            output_capacity,
             last_trade: Trade::default(),
             running_resources,
             time_remaining: 0,
             time_change: 0,
+            production_remaining: 0,
+            halted: false,
+            durability: MAX_DURABILITY,
+            wear,
             sender: None,
             screen_sender: None,
         }
@@ -119,40 +187,63 @@ impl Machine {
 
     /// Alternative new constructor for the machine using one parameter tuple
     /// # Arguments
-    /// * `(name, hit_box, trades, running_resources)` - a tuple containing the same arguments as `new()`
+    /// * `(name, hit_box, trades, recipes, running_resources, wear, output_capacity)` - a tuple containing the same arguments as `new()`
     /// # Returns
     /// * 'Machine'
     pub(crate) fn new_by_const(
-        (name, hit_box, trades, running_resources): (String, Rect, Vec<Trade>, Resources<i16>),
+        (name, hit_box, trades, recipes, running_resources, wear, output_capacity): (
+            String,
+            Rect,
+            Vec<Trade>,
+            Vec<Recipe>,
+            Resources<i16>,
+            u16,
+            u16,
+        ),
     ) -> Self {
-        Machine::new(name, hit_box, trades, running_resources)
+        Machine::new(
+            name,
+            hit_box,
+            trades,
+            recipes,
+            running_resources,
+            wear,
+            output_capacity,
+        )
     }
 
-    /// Initializes the machine with the data that is not Serialize
-    /// This function is required to be called before the first draw call
+    /// Initializes the machine with the senders that are not Serialize
+    /// This function is required to be called before the first tick
     /// # Arguments
-    /// * `images` - A Slice of Images containing the sprites for this Machine
     /// * `sender` - A sender of type `Sender<GameCommand>`
     /// * `screen_sender` - A sender of type `Sender<StackCommand>`
-    pub(crate) fn init(
-        &mut self,
-        images: &[Image],
-        sender: Sender<GameCommand>,
-        screen_sender: Sender<StackCommand>,
-    ) {
-        self.sprite = Some(images.into());
+    pub fn init(&mut self, sender: Sender<GameCommand>, screen_sender: Sender<StackCommand>) {
         self.sender = Some(sender);
         self.screen_sender = Some(screen_sender);
-        if self.name == "Loch" {
-            // Constant (pos of hole)
-            if self.hitbox.x == 780. {
-                self.change_state_to(&Running);
-            } else {
-                self.change_state_to(&Idle);
-            }
+        if let Some(state) = self.start_state.take() {
+            self.change_state_to(&state);
         }
     }
 
+    /// Sets the sprites of the machine, this is required to be called before the first draw call
+    /// # Arguments
+    /// * `images` - A Slice of Images containing the sprites for this Machine
+    pub(crate) fn set_sprite(&mut self, images: &[Image]) {
+        self.sprite = Some(images.into());
+    }
+
+    /// Moves the machine to its position in the level
+    /// # Arguments
+    /// * `position` - The position of the top left corner of the machine
+    /// * `start_state` - The state the machine is set to once it is initialized
+    pub(crate) fn place(&mut self, position: (f32, f32), start_state: Option<State>) {
+        self.hitbox.x = position.0;
+        self.hitbox.y = position.1;
+        self.interaction_area.x = position.0 - PLAYER_INTERACTION_RADIUS;
+        self.interaction_area.y = position.1 - PLAYER_INTERACTION_RADIUS;
+        self.start_state = start_state;
+    }
+
     /// Fetches the correct sprite depending on the current sate
     /// # Returns
     /// * `&Image` - a reference to the graphic
@@ -164,6 +255,11 @@ impl Machine {
     /// # Returns
     /// * `0...1` - '1' being timer just started equal to 100% and -1 for no Timer
     pub(crate) fn get_time_percentage(&self) -> f32 {
+        if self.production_remaining > 0 {
+            return self.current_recipe().map_or(-1.0, |recipe| {
+                f32::from(self.production_remaining) / f32::from(recipe.time_ticks.max(1))
+            });
+        }
         if self.last_trade.time_ticks == 0 {
             -1.0
         } else {
@@ -171,6 +267,28 @@ impl Machine {
         }
     }
 
+    /// Returns whether a brownout or a breakdown stopped the production of the queue and the
+    /// machine waits for the player to resume it. A broken machine has to be repaired first.
+    pub(crate) fn is_halted(&self) -> bool {
+        self.halted && !self.queue.is_empty() && self.state != Broken
+    }
+
+    /// Returns whether the timer of a trade is running or the machine works through its queue
+    pub(crate) fn is_busy(&self) -> bool {
+        self.time_change != 0 || (!self.queue.is_empty() && !self.halted)
+    }
+
+    /// Returns the recipe the machine produces next
+    fn current_recipe(&self) -> Option<&Recipe> {
+        let (name, _) = self.queue.first()?;
+        self.recipes.iter().find(|recipe| recipe.name == *name)
+    }
+
+    /// Returns the amount of units left in the queue
+    pub(crate) fn queued_units(&self) -> u16 {
+        self.queue.iter().map(|(_, amount)| amount).sum()
+    }
+
     /// Determines if the Player can interact with this Machine
     /// # Arguments
     /// * `pos` - a tuples of x and y containing the player position
@@ -181,48 +299,160 @@ impl Machine {
         is_colliding(pos, &self.interaction_area)
     }
 
-    /// Handles the interaction of the machine and the player
+    /// Returns the actions the player can choose from in the current state of the machine:
+    /// the recipes, which can be queued unless the machine is broken, followed by the trades
+    /// starting in this state. While the timer of a trade runs, it can only be cancelled instead.
+    /// The production comes first, so that maintaining a machine is never chosen by accident.
+    pub(crate) fn options(&self) -> Vec<MachineOption> {
+        let trades = if self.time_change == 0 {
+            self.trades
+                .iter()
+                .enumerate()
+                .filter(|(_, trade)| trade.initial_state == self.state && trade.name != "no_Trade")
+                .map(|(i, _)| MachineOption::Trade(i))
+                .collect()
+        } else {
+            vec![MachineOption::Cancel]
+        };
+        let recipes = (0..self.recipes.len())
+            .filter(|_| self.state != Broken)
+            .map(MachineOption::Recipe);
+        recipes.chain(trades).collect()
+    }
+
+    /// Describes an option with its name, the items the player pays, the items the player gets
+    /// and its duration. Trades are described like recipes.
+    /// # Arguments
+    /// * `option` - The option to describe
+    /// # Returns
+    /// * `Option<Recipe>` - The description, `None` if the machine has no such option
+    pub(crate) fn describe(&self, option: MachineOption) -> Option<Recipe> {
+        match option {
+            MachineOption::Trade(index) => self.trades.get(index).map(|trade| {
+                let (inputs, outputs): (Vec<_>, Vec<_>) = trade
+                    .cost
+                    .iter()
+                    .cloned()
+                    .partition(|(_, amount)| *amount >= 0);
+                Recipe::new(
+                    trade.name.clone(),
+                    inputs,
+                    outputs
+                        .into_iter()
+                        .map(|(item, amount)| (item, -amount))
+                        .collect(),
+                    trade.time_ticks,
+                )
+            }),
+            MachineOption::Recipe(index) => self.recipes.get(index).cloned(),
+            MachineOption::Cancel => {
+                Some(Recipe::new("cancel".to_string(), vec![], self.refund(), 0))
+            }
+        }
+    }
+
+    /// Handles the interaction of the machine and the player by choosing the first option.
+    /// A halted production is resumed instead, without running any trade.
     /// # Arguments
     /// * `player` - a reference to the player
-    pub(crate) fn interact(&mut self, player: &Player) -> RLResult {
-        // Check if there is a possible trade
-        let trade = match self.trades.iter().find(|t| t.initial_state == self.state) {
-            Some(t) => t.clone(),
-            None => return Ok(()),
-        };
+    /// * `items` - the item registry, used to show the names of missing items
+    pub(crate) fn interact(&mut self, player: &Player, items: &ItemRegistry) -> RLResult {
+        if self.is_halted() {
+            info!("Resuming the production of {}", self.name);
+            self.halted = false;
+            return Ok(());
+        }
+        if let Some(option) = self.options().first().copied() {
+            self.choose(option, player, items)?;
+        }
+        Ok(())
+    }
+
+    /// Executes an option the player has chosen
+    /// # Arguments
+    /// * `option` - The trade to execute or the recipe to queue
+    /// * `player` - a reference to the player
+    /// * `items` - the item registry, used to show the names of missing items
+    pub(crate) fn choose(
+        &mut self,
+        option: MachineOption,
+        player: &Player,
+        items: &ItemRegistry,
+    ) -> RLResult {
+        self.halted = false;
+        match option {
+            MachineOption::Trade(index) => match self.trades.get(index).cloned() {
+                Some(trade) => self.execute_trade(&trade, player, items),
+                None => Ok(()),
+            },
+            MachineOption::Recipe(index) => match self.recipes.get(index) {
+                Some(recipe) => {
+                    let name = recipe.name.clone();
+                    self.queue_recipe(&name, player, items)
+                }
+                None => Ok(()),
+            },
+            MachineOption::Cancel => self.cancel(),
+        }
+    }
+
+    /// Returns the items the player gets back if the running trade is cancelled
+    fn refund(&self) -> Vec<(String, i32)> {
+        let refund = i32::from(self.last_trade.refund.min(100));
+        self.last_trade
+            .cost
+            .iter()
+            .map(|(item, demand)| (item.clone(), demand * refund / 100))
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
 
-        if trade.name == *"no_Trade" {
+    /// Cancels the running trade. The timer is stopped, the machine returns to the state it was in
+    /// before the trade and the player gets back the refund of the trade.
+    /// The state is changed like every other state change, so that the resources the machine
+    /// changes while running are given back as well.
+    pub(crate) fn cancel(&mut self) -> RLResult {
+        if self.time_change == 0 {
+            return Ok(());
+        }
+        info!("Cancelling trade:{} ", self.last_trade.name);
+        self.time_change = 0;
+        self.time_remaining = 0;
+        if self.last_trade.return_after_timer {
+            self.change_state_to(&self.last_trade.initial_state.clone());
+        }
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(GameCommand::AddItems(self.refund()))?;
+        Ok(())
+    }
+
+    /// Executes a trade if the player can afford it
+    /// # Arguments
+    /// * `trade` - The trade to execute
+    /// * `player` - a reference to the player
+    /// * `items` - the item registry, used to show the names of missing items
+    fn execute_trade(&mut self, trade: &Trade, player: &Player, items: &ItemRegistry) -> RLResult {
+        if trade.initial_state != self.state || trade.name == *"no_Trade" {
             return Ok(());
         }
         // Check if the player has energy (and its needed)
-        if player.resources.energy == 0 && self.running_resources.energy < 0 && self.name != "Loch"
-        {
+        if player.resources.energy == 0 && self.is_consumer() {
             return Ok(());
         }
-        // dif = the different between items the player has and the cost of the trade
-        let dif = trade
-            .cost
-            .iter()
-            .map(|(item, demand)| (item, player.get_item_amount(item) - demand))
-            .filter(|(_item, dif)| *dif < 0)
-            .collect::<Vec<(&Item, i32)>>();
-        // If one item is not available in enough quantity inform the player and cancel the interaction
-        if dif.iter().any(|(_, demand)| *demand < 0) {
-            let mut missing_items = String::new();
-            dif.iter()
-                .map(|(item, amount)| format!("*{} {}\n", amount * -1, item.name))
-                .for_each(|x| missing_items.push_str(&x));
-            let popup = Popup::info(format!("{}\n{missing_items}", TRADE_CONFLICT_POPUP[0]));
-            info!(
-                "Popup for Trade conflict sent: Missing Items: {}",
-                missing_items
-            );
+        if self.buffered_units() + trade.output_units() > i32::from(self.output_capacity) {
+            // The outputs of the trade would not fit into the output buffer
+            let popup = Popup::warning(tr!("machine.output_full", machine = self.name));
             self.screen_sender
                 .as_ref()
                 .unwrap()
                 .send(StackCommand::Popup(popup))?;
             return Ok(());
         }
+        if !self.can_afford(&trade.cost, player, items)? {
+            return Ok(());
+        }
 
         // At this point all checks have passed and continue with executing the trade
         info!("Executing trade:{} ", trade.name);
@@ -233,7 +463,7 @@ impl Machine {
             .iter()
             .filter(|(_, demand)| *demand >= 0)
             .map(|(item, demand)| (item.clone(), -*demand))
-            .collect::<Vec<(Item, i32)>>();
+            .collect::<Vec<(String, i32)>>();
         self.sender
             .as_ref()
             .unwrap()
@@ -242,6 +472,9 @@ impl Machine {
         if trade.time_ticks == 0 {
             // this trade has no timer
             self.time_change = 0;
+            if trade.restores_durability {
+                self.durability = MAX_DURABILITY;
+            }
         } else {
             //this trade has a timer
             if self.time_remaining == 0 {
@@ -260,6 +493,103 @@ impl Machine {
         Ok(())
     }
 
+    /// Returns whether the player owns all items an option costs
+    /// # Arguments
+    /// * `option` - The option to check
+    /// * `player` - a reference to the player
+    pub(crate) fn is_affordable(&self, option: MachineOption, player: &Player) -> bool {
+        self.describe(option).map_or(false, |offer| {
+            offer
+                .inputs
+                .iter()
+                .all(|(item, amount)| player.get_item_amount(item) >= *amount)
+        })
+    }
+
+    /// Checks whether the player has all items of a cost and informs the player about the missing ones
+    /// # Arguments
+    /// * `cost` - The ids and amounts of the items, negative amounts are given to the player
+    /// * `player` - a reference to the player
+    /// * `items` - the item registry, used to show the names of missing items
+    /// # Returns
+    /// * `RLResult<bool>` - Whether the player can afford the cost
+    fn can_afford(
+        &self,
+        cost: &[(String, i32)],
+        player: &Player,
+        items: &ItemRegistry,
+    ) -> RLResult<bool> {
+        // dif = the different between items the player has and the cost
+        let dif = cost
+            .iter()
+            .map(|(item, demand)| (item, player.get_item_amount(item) - demand))
+            .filter(|(_item, dif)| *dif < 0)
+            .collect::<Vec<(&String, i32)>>();
+        // If one item is not available in enough quantity inform the player
+        if dif.iter().any(|(_, demand)| *demand < 0) {
+            let mut missing_items = String::new();
+            let missing = dif.iter().filter(|(_, demand)| *demand < 0).count();
+            dif.iter()
+                .map(|(item, amount)| {
+                    tr!(
+                        "trade.missing_item",
+                        count = amount * -1,
+                        item = items.name(item)
+                    ) + "\n"
+                })
+                .for_each(|x| missing_items.push_str(&x));
+            let popup = Popup::info(format!(
+                "{}\n{missing_items}",
+                tr!("trade.missing_items", count = missing)
+            ));
+            info!(
+                "Popup for Trade conflict sent: Missing Items: {}",
+                missing_items
+            );
+            self.screen_sender
+                .as_ref()
+                .unwrap()
+                .send(StackCommand::Popup(popup))?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Queues one unit of a recipe, the player pays its inputs right away
+    /// # Arguments
+    /// * `name` - The name of the recipe
+    /// * `player` - a reference to the player
+    /// * `items` - the item registry, used to show the names of missing items
+    pub(crate) fn queue_recipe(
+        &mut self,
+        name: &str,
+        player: &Player,
+        items: &ItemRegistry,
+    ) -> RLResult {
+        let Some(recipe) = self.recipes.iter().find(|recipe| recipe.name == name) else {
+            return Ok(());
+        };
+        if !self.can_afford(&recipe.inputs, player, items)? {
+            return Ok(());
+        }
+        info!("Queueing recipe:{} ", recipe.name);
+        let inputs = recipe
+            .inputs
+            .iter()
+            .map(|(item, amount)| (item.clone(), -*amount))
+            .collect::<Vec<(String, i32)>>();
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(GameCommand::AddItems(inputs))?;
+        if let Some((_, amount)) = self.queue.last_mut().filter(|(last, _)| last == name) {
+            *amount += 1;
+        } else {
+            self.queue.push((name.to_string(), 1));
+        }
+        Ok(())
+    }
+
     /// Handels the timer by being called every tick
     pub(crate) fn tick(&mut self) -> RLResult {
         self.time_remaining -= self.time_change;
@@ -270,34 +600,121 @@ impl Machine {
             self.time_remaining = 0;
 
             if self.last_trade.return_after_timer {
-                // handel edge case for wining the game
-                if self.last_trade.name == "Notfall_signal_absetzen" {
-                    return Ok(self.sender.as_ref().unwrap().send(GameCommand::Winning)?);
-                }
                 self.change_state_to(&self.last_trade.initial_state.clone());
             } else {
                 self.change_state_to(&self.last_trade.resulting_state.clone());
             }
-            // After Trade ended, send the GameCommand AddItems to add the earning Items to the players inventory
+            if self.last_trade.restores_durability {
+                self.durability = MAX_DURABILITY;
+            }
+            // After Trade ended, the earned items wait in the output buffer until the player collects them
             let trade = self.last_trade.clone();
             let items = trade
                 .cost
                 .iter()
                 .filter(|(_, demand)| *demand < 0)
                 .map(|(item, demand)| (item.clone(), -*demand))
-                .collect::<Vec<(Item, i32)>>();
+                .collect::<Vec<(String, i32)>>();
+            self.store_output(&items);
+            // Inform the objectives about the finished trade
             self.sender
                 .as_ref()
                 .unwrap()
-                .send(GameCommand::AddItems(items))?;
+                .send(GameCommand::TradeCompleted(trade.name))?;
+        }
+        self.produce()
+    }
+
+    /// Works through the queue, called every tick. The machine runs while it produces a unit
+    /// and becomes idle once the queue is empty.
+    fn produce(&mut self) -> RLResult {
+        if self.production_remaining > 0 {
+            if self.state != Running {
+                // Something else stopped the machine, the unit has to be started again
+                self.production_remaining = 0;
+                self.halted = true;
+                return Ok(());
+            }
+            self.production_remaining -= 1;
+            if self.production_remaining > 0 {
+                return Ok(());
+            }
+            self.finish_unit()?;
+        } else if self.halted || self.time_change != 0 || self.state != Idle {
+            return Ok(());
+        }
+        // Start the next unit
+        while !self.queue.is_empty() {
+            if let Some(recipe) = self.current_recipe() {
+                if self.buffered_units() + recipe.output_units() > i32::from(self.output_capacity) {
+                    // The production continues once the player collects the output buffer
+                    break;
+                }
+                info!("Producing recipe:{} ", recipe.name);
+                self.production_remaining = recipe.time_ticks.max(1);
+                self.change_state_to(&Running);
+                return Ok(());
+            }
+            let (name, _) = self.queue.remove(0);
+            warn!("{} can not produce the unknown recipe {}", self.name, name);
         }
+        if self.state == Running {
+            self.change_state_to(&Idle);
+        }
+        Ok(())
+    }
+
+    /// Gives the outputs of the finished unit to the player and removes it from the queue
+    fn finish_unit(&mut self) -> RLResult {
+        let Some(recipe) = self.current_recipe().cloned() else {
+            return Ok(());
+        };
+        if let Some((_, amount)) = self.queue.first_mut().filter(|(_, amount)| *amount > 1) {
+            *amount -= 1;
+        } else {
+            self.queue.remove(0);
+        }
+        self.store_output(&recipe.outputs);
+        // Inform the objectives about the finished unit
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(GameCommand::TradeCompleted(recipe.name))?;
         Ok(())
     }
 
+    /// Puts finished items into the output buffer
+    /// # Arguments
+    /// * `items` - The ids and amounts of the items
+    fn store_output(&mut self, items: &[(String, i32)]) {
+        for (id, amount) in items {
+            if let Some((_, stored)) = self.output.iter_mut().find(|(item, _)| item == id) {
+                *stored += amount;
+            } else {
+                self.output.push((id.clone(), *amount));
+            }
+        }
+    }
+
+    /// Returns the amount of items in the output buffer
+    pub(crate) fn buffered_units(&self) -> i32 {
+        self.output.iter().map(|(_, amount)| amount).sum()
+    }
+
+    /// Returns whether the output buffer can not hold another unit of the recipe produced next
+    pub(crate) fn is_output_full(&self) -> bool {
+        self.current_recipe().map_or(
+            self.buffered_units() >= i32::from(self.output_capacity),
+            |recipe| {
+                self.buffered_units() + recipe.output_units() > i32::from(self.output_capacity)
+            },
+        )
+    }
+
     /// Used to change the State of the Machine gracefully
     /// # Arguments
     /// * `new_state` - the state that the machine should change to
-    pub(crate) fn change_state_to(&mut self, new_state: &State) {
+    pub fn change_state_to(&mut self, new_state: &State) {
         if self.state != *new_state {
             self.invoke_state_change(&self.state, new_state);
             if self.state == Running && self.time_change == 1 {
@@ -306,12 +723,86 @@ impl Machine {
             self.state = new_state.clone();
         }
     }
+    /// Returns the durability of the machine as a fraction of its full durability
+    pub(crate) fn get_durability_percentage(&self) -> f32 {
+        f32::from(self.durability) / f32::from(MAX_DURABILITY)
+    }
+
+    /// Returns whether the machine wears down while running
+    pub(crate) fn wears_down(&self) -> bool {
+        self.wear > 0
+    }
+
+    /// Wears the machine down by one second of running, machines which are not running keep their condition
+    /// # Returns
+    /// * `usize` - The chance to break down in this second out of `FAILURE_ROLL`, it is 0 above
+    ///   `DURABILITY_THRESHOLD` and rises the more the machine is worn down
+    pub(crate) fn wear_down(&mut self) -> usize {
+        if self.state != Running || self.wear == 0 {
+            return 0;
+        }
+        self.durability = self.durability.saturating_sub(self.wear);
+        usize::from(DURABILITY_THRESHOLD.saturating_sub(self.durability))
+    }
+
+    /// Breaks the machine down, a running trade is cancelled and the player is informed
+    pub(crate) fn break_down(&mut self) -> RLResult {
+        info!("Machine {} broke down", self.name);
+        self.time_change = 0;
+        self.time_remaining = 0;
+        self.production_remaining = 0;
+        self.halted = true;
+        self.change_state_to(&Broken);
+        let popup = Popup::warning(tr!("machine.broke_down", machine = self.name));
+        self.screen_sender
+            .as_ref()
+            .unwrap()
+            .send(StackCommand::Popup(popup))?;
+        Ok(())
+    }
+
+    /// Returns the energy the machine produces per tick while running, it is negative for consumers
+    pub(crate) fn get_energy(&self) -> i16 {
+        self.running_resources.energy
+    }
+
+    /// Returns whether the machine draws power from the grid while running.
+    /// Holes leak energy, but are no part of the grid.
+    pub(crate) fn is_consumer(&self) -> bool {
+        self.running_resources.energy < 0 && self.name != "Loch"
+    }
+
+    /// Returns the oxygen the machine adds to its room per tick while running, it is negative for
+    /// machines which use up or leak the air of their room
+    pub(crate) fn get_oxygen(&self) -> i16 {
+        self.running_resources.oxygen
+    }
+
+    /// Returns the center of the machine, which decides the room the machine stands in
+    pub(crate) fn center(&self) -> (f32, f32) {
+        (
+            self.hitbox.x + self.hitbox.w / 2.,
+            self.hitbox.y + self.hitbox.h / 2.,
+        )
+    }
+
+    /// Returns the resources the machine changes for the whole game while running.
+    /// The oxygen is left out, because it only changes the atmosphere of the room of the machine.
+    fn grid_resources(&self) -> Resources<i16> {
+        Resources {
+            oxygen: 0,
+            ..self.running_resources
+        }
+    }
+
     /// A helper funktion to disable every funktion in case there is no energy in the system
     pub(crate) fn no_energy(&mut self) {
-        if self.running_resources.energy < 0 && self.name != "Loch" {
+        if self.is_consumer() {
             // If there is no energy available but this machine needs some, stop this machine.
             if self.state == Running {
                 self.change_state_to(&Idle);
+                self.production_remaining = 0;
+                self.halted = true;
             }
         }
     }
@@ -323,17 +814,13 @@ impl Machine {
     /// * `after` - the state that it will be in after the change is complete
     fn invoke_state_change(&self, before: &State, after: &State) {
         match (before, after) {
-            (Broken, Idle) => {
-                let _e = self.sender.as_ref().unwrap().send(GameCommand::Milestone);
-            }
-            (Idle, Broken) => {}
+            (Broken, Idle) | (Idle, Broken) => {}
             (Broken | Idle, Running) => {
                 let _e = self
                     .sender
                     .as_ref()
                     .unwrap()
-                    .send(GameCommand::ResourceChange(self.running_resources));
-                let _e = self.sender.as_ref().unwrap().send(GameCommand::Milestone);
+                    .send(GameCommand::ResourceChange(self.grid_resources()));
             }
             (Running, Broken | Idle) => {
                 let _e = self
@@ -345,7 +832,7 @@ impl Machine {
                             oxygen: 0,
                             energy: 0,
                             life: 0,
-                        } - self.running_resources,
+                        } - self.grid_resources(),
                     ));
             }
             _ => {
@@ -1,3 +1,7 @@
-pub(crate) mod machine;
+pub(crate) mod catalog;
+pub(crate) mod interaction_menu;
+pub mod machine;
 pub(crate) mod machine_sprite;
+pub(crate) mod power_grid;
+pub(crate) mod recipe;
 pub(crate) mod trade;
@@ -0,0 +1,358 @@
+//! Contains the power grid, which balances the energy produced by the generators against the
+//! energy requested by the consumers and decides which machines are shed in a brownout.
+use crate::backend::constants::{BATTERY_OUTPUT, GRID_POSITION};
+use crate::backend::gamestate::GameState;
+use crate::backend::rlcolor::RLColor;
+use crate::backend::screen::{Popup, StackCommand};
+use crate::backend::utils::get_draw_params;
+use crate::machines::machine::{Machine, State};
+use crate::{draw, tr, RLResult};
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, DrawMode, Mesh, Rect, Text, TextFragment};
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// The power grid of the station. The generators supply it, the consumers and the station itself
+/// put a load on it. The energy resource of the player is the battery of the grid, which buffers
+/// it: the battery is charged by the surplus and adds up to `BATTERY_OUTPUT` to the supply while
+/// it is charged. Whenever the demand exceeds the supply, the consumers with the lowest priority
+/// are shed until the grid can carry the rest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PowerGrid {
+    /// The names of the consumers ordered by priority, the first one is the last to be shed.
+    /// Consumers which are not listed follow in the order of the machines.
+    #[serde(default)]
+    pub(crate) priorities: Vec<String>,
+}
+
+impl PowerGrid {
+    /// Returns the energy per tick the running generators feed into the grid
+    /// # Arguments
+    /// * `machines` - The machines of the game
+    fn generation(machines: &[Machine]) -> i16 {
+        machines
+            .iter()
+            .filter(|machine| machine.state == State::Running && machine.get_energy() > 0)
+            .map(Machine::get_energy)
+            .sum()
+    }
+
+    /// Returns the energy per tick the running generators and the battery can supply
+    /// # Arguments
+    /// * `machines` - The machines of the game
+    /// * `battery` - The energy of the player, which is stored in the battery
+    pub(crate) fn supply(machines: &[Machine], battery: u16) -> i16 {
+        let battery_output = if battery > 0 { BATTERY_OUTPUT } else { 0 };
+        PowerGrid::generation(machines) + battery_output
+    }
+
+    /// Returns the energy per tick the running consumers, the station and the leaks request from
+    /// the grid. It is the energy the battery would lose per tick without the generators.
+    /// # Arguments
+    /// * `machines` - The machines of the game
+    /// * `energy_change` - The change of the energy of the player per tick
+    pub(crate) fn demand(machines: &[Machine], energy_change: i16) -> i16 {
+        PowerGrid::generation(machines) - energy_change
+    }
+
+    /// Returns the names of the consumers ordered by their priority, the most important first
+    /// # Arguments
+    /// * `machines` - The machines of the game
+    pub(crate) fn consumers(&self, machines: &[Machine]) -> Vec<String> {
+        let mut consumers = self
+            .priorities
+            .iter()
+            .filter(|name| machines.iter().any(|m| m.is_consumer() && m.name == **name))
+            .cloned()
+            .collect::<Vec<String>>();
+        for machine in machines.iter().filter(|machine| machine.is_consumer()) {
+            if !consumers.contains(&machine.name) {
+                consumers.push(machine.name.clone());
+            }
+        }
+        consumers
+    }
+
+    /// Raises the priority of a consumer by one place
+    /// # Arguments
+    /// * `machines` - The machines of the game
+    /// * `index` - The place of the consumer in the current order, starting at 0
+    pub(crate) fn raise(&mut self, machines: &[Machine], index: usize) {
+        let mut consumers = self.consumers(machines);
+        if index == 0 || index >= consumers.len() {
+            return;
+        }
+        consumers.swap(index - 1, index);
+        info!("Raised the grid priority of {}", consumers[index - 1]);
+        self.priorities = consumers;
+    }
+
+    /// Sheds the running consumers with the lowest priority until the generators can carry the
+    /// remaining demand
+    /// # Arguments
+    /// * `machines` - The machines of the game
+    /// * `battery` - The energy of the player, which is stored in the battery
+    /// * `energy_change` - The change of the energy of the player per tick
+    /// # Returns
+    /// * `Vec<String>` - The names of the machines which were shed
+    pub(crate) fn shed_load(
+        &self,
+        machines: &mut [Machine],
+        battery: u16,
+        energy_change: i16,
+    ) -> Vec<String> {
+        let supply = PowerGrid::supply(machines, battery);
+        let mut demand = PowerGrid::demand(machines, energy_change);
+        let mut shed = vec![];
+        for name in self.consumers(machines).iter().rev() {
+            if demand <= supply {
+                break;
+            }
+            for machine in machines
+                .iter_mut()
+                .filter(|machine| machine.name == *name && machine.state == State::Running)
+            {
+                demand += machine.get_energy();
+                machine.no_energy();
+                shed.push(machine.name.clone());
+            }
+        }
+        shed
+    }
+}
+
+impl GameState {
+    /// Sheds consumers while the demand exceeds the supply and informs the player about the brownout
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the brownout
+    pub(crate) fn brownout(&mut self) -> RLResult {
+        let shed = self.grid.shed_load(
+            &mut self.machines,
+            self.player.resources.energy,
+            self.player.resources_change.energy,
+        );
+        if shed.is_empty() {
+            return Ok(());
+        }
+        info!("Brownout, shed {:?}", shed);
+        let popup = Popup::warning(tr!("grid.brownout", machines = shed.join(", ")));
+        self.get_screen_sender()?.send(StackCommand::Popup(popup))?;
+        Ok(())
+    }
+
+    /// Draws the grid status panel with the supply, the demand and the consumers in the order in
+    /// which they are shed last
+    /// # Arguments
+    /// * `canvas`: The canvas to draw on
+    /// * `scale`: The scale of the canvas
+    /// * `ctx`: The `Context` of the game
+    /// # Returns
+    /// * `RLResult`: A `RLResult` to validate the success of the paint function
+    pub(crate) fn draw_grid(
+        &self,
+        canvas: &mut Canvas,
+        scale: Vec2,
+        ctx: &mut Context,
+    ) -> RLResult {
+        let consumers = self.grid.consumers(&self.machines);
+        let background = Mesh::new_rounded_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., 520., 190. + 30. * consumers.len() as f32),
+            10.,
+            RLColor::SHADOW,
+        )?;
+        draw!(canvas, &background, Vec2::from(GRID_POSITION), scale);
+        let supply = PowerGrid::supply(&self.machines, self.player.resources.energy);
+        let demand = PowerGrid::demand(&self.machines, self.player.resources_change.energy);
+        let mut lines = vec![
+            (tr!("grid.title"), RLColor::GOLD),
+            (
+                format!(
+                    "{}: {supply}   {}: {demand}",
+                    tr!("grid.supply"),
+                    tr!("grid.demand")
+                ),
+                if demand > supply {
+                    RLColor::STATUS_RED
+                } else {
+                    RLColor::STATUS_GREEN
+                },
+            ),
+            (
+                format!(
+                    "{}: {:.1}",
+                    tr!("grid.battery"),
+                    f32::from(self.player.resources.energy) / f32::from(u16::MAX) * 100.
+                ),
+                RLColor::LIGHT_GREY,
+            ),
+        ];
+        for (i, name) in consumers.iter().enumerate() {
+            let running = self
+                .machines
+                .iter()
+                .any(|machine| machine.name == *name && machine.state == State::Running);
+            let load = self
+                .machines
+                .iter()
+                .find(|machine| machine.name == *name)
+                .map_or(0, |machine| -machine.get_energy());
+            let color = if running {
+                RLColor::STATUS_GREEN
+            } else {
+                RLColor::LIGHT_GREY
+            };
+            lines.push((format!("{}. {name} ({load})", i + 1), color));
+        }
+        lines.push((tr!("grid.hint"), RLColor::LIGHT_GREY));
+        for (i, (line, color)) in lines.into_iter().enumerate() {
+            let text = Text::new(TextFragment::new(line).color(color).scale(24.));
+            draw!(
+                canvas,
+                &text,
+                Vec2::new(
+                    GRID_POSITION.0 + 20.,
+                    GRID_POSITION.1 + 20. + 30. * i as f32
+                ),
+                scale
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::constants::DESIRED_FPS;
+
+    #[test]
+    fn test_raise_priority() {
+        let (gamestate, _receiver) = GameState::test_game(1);
+        let mut grid = PowerGrid::default();
+        let consumers = grid.consumers(&gamestate.machines);
+        assert!(!consumers.contains(&"Stromgenerator".to_string()));
+        assert!(!consumers.contains(&"Loch".to_string()));
+        grid.raise(&gamestate.machines, 1);
+        assert_eq!(grid.consumers(&gamestate.machines)[0], consumers[1]);
+        assert_eq!(grid.consumers(&gamestate.machines)[1], consumers[0]);
+        // The first consumer can not be raised any further
+        grid.raise(&gamestate.machines, 0);
+        assert_eq!(grid.consumers(&gamestate.machines)[0], consumers[1]);
+    }
+
+    /// Returns the change of the energy of the player once the running machines are accounted for,
+    /// the station itself draws one unit
+    fn energy_change(machines: &[Machine]) -> i16 {
+        machines
+            .iter()
+            .filter(|machine| machine.state == State::Running)
+            .map(Machine::get_energy)
+            .sum::<i16>()
+            - 1
+    }
+
+    #[test]
+    fn test_shed_lowest_priority() {
+        let (mut gamestate, _receiver) = GameState::test_game(1);
+        let mut grid = PowerGrid::default();
+        let consumers = grid.consumers(&gamestate.machines);
+        for machine in &mut gamestate.machines {
+            if machine.is_consumer() {
+                machine.change_state_to(&State::Running);
+            }
+        }
+        // Without a generator and an empty battery every consumer is shed
+        let change = energy_change(&gamestate.machines);
+        assert_eq!(
+            grid.shed_load(&mut gamestate.machines, 0, change).len(),
+            consumers.len()
+        );
+
+        for machine in &mut gamestate.machines {
+            if machine.is_consumer() {
+                machine.change_state_to(&State::Running);
+            }
+        }
+        let change = energy_change(&gamestate.machines);
+        let battery = u16::MAX;
+        assert!(
+            PowerGrid::demand(&gamestate.machines, change)
+                > PowerGrid::supply(&gamestate.machines, battery)
+        );
+        // Move the last consumer to the top, so that it is kept running
+        let last = consumers.len() - 1;
+        for i in (1..=last).rev() {
+            grid.raise(&gamestate.machines, i);
+        }
+        let shed = grid.shed_load(&mut gamestate.machines, battery, change);
+        assert!(!shed.is_empty());
+        assert!(!shed.contains(&consumers[last]));
+        assert_eq!(shed[0], consumers[last - 1]);
+        let change = energy_change(&gamestate.machines);
+        assert!(
+            PowerGrid::demand(&gamestate.machines, change)
+                <= PowerGrid::supply(&gamestate.machines, battery)
+        );
+    }
+
+    #[test]
+    fn test_battery_carries_start_load() {
+        let (mut gamestate, receiver) = GameState::test_game(1);
+        assert_eq!(gamestate.player.resources.energy, u16::MAX);
+        for name in ["Sauerstoffgenerator", "Werkermaschine"] {
+            let index = gamestate.machine_index(name);
+            gamestate.machines[index].change_state_to(&State::Running);
+        }
+        for _ in 0..DESIRED_FPS {
+            gamestate.tick().unwrap();
+        }
+        // The full battery carries the consumers of the start without a generator
+        assert!(gamestate
+            .machines
+            .iter()
+            .filter(
+                |machine| machine.name == "Sauerstoffgenerator" || machine.name == "Werkermaschine"
+            )
+            .all(|machine| machine.state == State::Running));
+        assert!(!receiver
+            .try_iter()
+            .any(|command| matches!(command, StackCommand::Popup(_))));
+    }
+
+    #[test]
+    fn test_battery_buffers_grid() {
+        let (mut gamestate, receiver) = GameState::test_game(1);
+        let oxygen = gamestate.machine_index("Sauerstoffgenerator");
+        for machine in &mut gamestate.machines {
+            if machine.is_consumer() {
+                machine.change_state_to(&State::Running);
+            }
+        }
+        for _ in 0..DESIRED_FPS {
+            gamestate.tick().unwrap();
+        }
+        // The charged battery alone can not carry every consumer, so the load is shed as soon as
+        // the grid is balanced and only the consumers of the start keep running
+        assert!(gamestate.player.resources.energy > u16::MAX / 2);
+        let running = gamestate
+            .machines
+            .iter()
+            .filter(|machine| machine.is_consumer() && machine.state == State::Running)
+            .map(|machine| machine.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            running,
+            vec![gamestate.machines[oxygen].name.as_str(), "Werkermaschine"]
+        );
+        assert!(receiver
+            .try_iter()
+            .any(|command| matches!(command, StackCommand::Popup(_))));
+        // The battery covers the remaining demand
+        let energy = gamestate.player.resources.energy;
+        gamestate.tick().unwrap();
+        assert!(gamestate.player.resources.energy < energy);
+    }
+}
@@ -0,0 +1,40 @@
+//! This File contains the structure `Recipe`
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A recipe is something a machine produces from items of the player.
+/// Unlike trades, recipes are queued: the player pays the inputs of every unit when queueing it
+/// and the machine produces the queued units one after another while running.
+pub struct Recipe {
+    /// Is used to queue the recipe and to inform the objectives once a unit is finished
+    pub name: String,
+    /// The ids and amounts of the items the player pays for one unit
+    #[serde(default)]
+    pub inputs: Vec<(String, i32)>,
+    /// The ids and amounts of the items the player gets for one unit
+    pub outputs: Vec<(String, i32)>,
+    /// The ticks it takes to produce one unit
+    pub(crate) time_ticks: i16,
+}
+
+impl Recipe {
+    /// Initialises a new Recipe using values passed in
+    pub fn new(
+        name: String,
+        inputs: Vec<(String, i32)>,
+        outputs: Vec<(String, i32)>,
+        time_ticks: i16,
+    ) -> Self {
+        Self {
+            name,
+            inputs,
+            outputs,
+            time_ticks,
+        }
+    }
+
+    /// Returns the amount of items one unit puts into the output buffer of the machine
+    pub(crate) fn output_units(&self) -> i32 {
+        self.outputs.iter().map(|(_, amount)| amount).sum()
+    }
+}
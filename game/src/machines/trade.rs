@@ -1,6 +1,6 @@
 //! This File contains the structure `Trade`
 //! Author: ["Sander Stella"]
-use crate::game_core::item::Item;
+use crate::backend::constants::DEFAULT_REFUND;
 use crate::machines::machine::State;
 use serde::{Deserialize, Serialize};
 
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 /// Trade itself does not contain any logic and is mostly a construct to group values
 pub struct Trade {
     /// is used for debugging and login purposes
-    pub(crate) name: String,
+    pub name: String,
     /// the time it takes for the trade to conclude 0 = instant
     pub time_ticks: i16,
     /// the Machine needs to be in `initial_state` for the trade to be accessible
@@ -22,10 +22,21 @@ pub struct Trade {
     /// * false = permanent, meaning the state is set to `resulting_state` after the timer
     pub return_after_timer: bool, // how the ms behaves after the timer run out
     /// Contains the cost associated with this trade.
-    /// This stores the amount of item the player loses and or gain.
+    /// This stores the id and amount of items the player loses and or gain.
     /// * Positive amount means the Player will **lose** these items.
     /// * Negative amount means the Player will **gain** these items.
-    pub(crate) cost: Vec<(Item, i32)>,
+    pub cost: Vec<(String, i32)>,
+    #[serde(default)]
+    /// Whether the machine is as good as new once the trade is finished, set for repairs and maintenance
+    pub(crate) restores_durability: bool,
+    #[serde(default = "default_refund")]
+    /// The percentage of the paid items the player gets back if the trade is cancelled
+    pub refund: u8,
+}
+
+/// Returns the percentage of the paid items returned by trades which do not set their own refund
+pub(crate) fn default_refund() -> u8 {
+    DEFAULT_REFUND
 }
 
 impl Default for Trade {
@@ -39,6 +50,8 @@ impl Default for Trade {
             resulting_state: State::Running,
             return_after_timer: false,
             cost: vec![],
+            restores_durability: false,
+            refund: DEFAULT_REFUND,
         }
     }
 }
@@ -51,7 +64,8 @@ impl Trade {
         initial_state: State,
         resulting_state: State,
         return_after_timer: bool,
-        cost: Vec<(Item, i32)>,
+        cost: Vec<(String, i32)>,
+        restores_durability: bool,
     ) -> Self {
         Self {
             name,
@@ -60,6 +74,17 @@ impl Trade {
             resulting_state,
             return_after_timer,
             cost,
+            restores_durability,
+            refund: DEFAULT_REFUND,
         }
     }
+
+    /// Returns the amount of items the trade puts into the output buffer of the machine
+    pub(crate) fn output_units(&self) -> i32 {
+        self.cost
+            .iter()
+            .filter(|(_, amount)| *amount < 0)
+            .map(|(_, amount)| -amount)
+            .sum()
+    }
 }
@@ -18,15 +18,17 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::cast_precision_loss)]
 #![allow(clippy::cast_possible_truncation)]
-mod backend;
-mod game_core;
-mod languages;
-mod machines;
-mod main_menu;
-
-use crate::backend::constants::SCREEN_RESOLUTION;
-use crate::backend::{error, screen::Screenstack};
 use chrono::Local;
+use game::backend::constants::{BOT_MAX_TICKS, DEFAULT_LEVEL_PATH, LANGUAGES, SCREEN_RESOLUTION};
+use game::backend::game_data::GameData;
+use game::backend::options::Options;
+use game::backend::recording::Recording;
+use game::backend::screen::Screenstack;
+use game::backend::settings::Settings;
+use game::backend::simulation::Simulation;
+use game::bots::scripted_agent::ScriptedAgent;
+use game::languages;
+use game::RLResult;
 
 #[cfg_attr(debug_assertions, allow(unused_imports))]
 use ggez::conf::FullscreenType;
@@ -35,11 +37,11 @@ use std::fs::File;
 use std::sync::Mutex;
 use tracing::{info, Level};
 
-/// Our own Result Type for custom Error handling.
-pub type RLResult<T = ()> = Result<T, error::RLError>;
 /// The main function, which is the entry point of our program
 /// builds the game and sets window configuration, icon and title
+/// The command line options are described in `Options`
 pub fn main() -> RLResult {
+    let options = Options::parse(std::env::args().skip(1))?;
     let cb = ggez::ContextBuilder::new("red-life", "red-life-project")
         .resources_dir_name("assets")
         .window_setup(
@@ -63,12 +65,73 @@ pub fn main() -> RLResult {
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
     // End logging
     info!("Starting Red Life");
+    let settings = Settings::load();
+    if languages::locale::set_language(&settings.language).is_err() {
+        languages::locale::set_language(LANGUAGES[0])?;
+    }
+    if let (Some(path), true) = (&options.replay, options.headless) {
+        return replay_headless(path);
+    }
+    if options.bot && options.headless {
+        return play_headless(options.seed.unwrap_or_else(|| fastrand::u64(..)));
+    }
     let (mut ctx, event_loop) = cb.build()?;
     info!("New Event Loop created");
     window_setup(&mut ctx)?;
-    let screen_stack = Screenstack::default();
+    if let Some(seed) = options.seed {
+        info!("Using seed {} for new games", seed);
+    }
+    let replay = options.replay.clone();
+    let mut screen_stack = Screenstack::new(options);
+    if let Some(path) = replay {
+        let recording = Recording::load(&path)?;
+        let mut gamestate = recording.start_state()?;
+        gamestate.init(&mut ctx)?;
+        gamestate.disable_saves = true;
+        gamestate.replayer = Some(recording.replayer());
+        screen_stack.push(Box::new(gamestate));
+    }
     event::run(ctx, event_loop, screen_stack);
 }
+/// Replays a recording without a window and prints whether it matches the recording
+/// # Arguments
+/// * `path` - The path of the recording
+/// # Returns
+/// * `RLResult` - Fails if the recording could not be loaded or does not match
+fn replay_headless(path: &str) -> RLResult {
+    let recording = Recording::load(path)?;
+    let simulation = recording.replay(GameData::load(&recording.level_path()?)?)?;
+    for notification in simulation.notifications() {
+        println!("{notification}");
+    }
+    println!(
+        "Replay of {} matches the recording: seed {}, ended at tick {}, outcome {:?}",
+        path,
+        recording.seed,
+        simulation.state().player.time,
+        simulation.outcome()
+    );
+    Ok(())
+}
+/// Lets the `ScriptedAgent` play a new game without a window and prints how it ended
+/// # Arguments
+/// * `seed` - The seed of the game
+/// # Returns
+/// * `RLResult` - Fails if the data files could not be loaded
+fn play_headless(seed: u64) -> RLResult {
+    let mut simulation = Simulation::new(GameData::load(DEFAULT_LEVEL_PATH)?, seed)?;
+    let outcome = simulation.run(&mut ScriptedAgent::default(), BOT_MAX_TICKS)?;
+    for notification in simulation.notifications() {
+        println!("{notification}");
+    }
+    println!(
+        "Bot game with seed {} ended at tick {}, outcome {:?}",
+        seed,
+        simulation.state().player.time,
+        outcome
+    );
+    Ok(())
+}
 /// Sets the window size to resizeable in debug mode and fullscreen mode for release mode
 fn window_setup(ctx: &mut Context) -> RLResult {
     ctx.gfx.set_resizable(true)?;
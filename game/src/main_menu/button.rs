@@ -7,26 +7,29 @@ use ggez::glam::f32::Vec2;
 use ggez::graphics::{Canvas, Color, Text, TextFragment};
 use ggez::mint::Point2;
 use ggez::{graphics, Context};
+use std::fmt::Debug;
 use std::sync::mpsc::Sender;
 use tracing::info;
 
-/// Clickable button
+/// Clickable button, which sends its message when it is clicked
 #[derive(Debug)]
-pub struct Button {
+pub struct Button<M = Message> {
     pub(crate) text: Text,
-    pub(crate) message: Message,
-    pub(crate) sender: Sender<Message>,
+    /// The size of the text
+    pub(crate) text_scale: f32,
+    pub(crate) message: M,
+    pub(crate) sender: Sender<M>,
     pub(crate) rect: graphics::Rect,
     pub(crate) color: Color,
     pub(crate) hover_color: Color,
     pub(crate) current_color: Color,
 }
 
-impl Button {
+impl<M: Copy + Debug> Button<M> {
     pub(crate) fn new(
         text: String,
-        message: Message,
-        sender: Sender<Message>,
+        message: M,
+        sender: Sender<M>,
         rect: graphics::Rect,
         color: Color,
         hover_color: Color,
@@ -34,6 +37,7 @@ impl Button {
         info!("New Button created: text: {}, message: {:?}", text, message);
         Self {
             text: Text::new(TextFragment::new(text).color(Color::BLACK)),
+            text_scale: 70.,
             message,
             sender,
             rect,
@@ -102,13 +106,16 @@ impl Button {
         );
 
         let text = &mut self.text.clone();
-        text.set_scale(70.);
+        text.set_scale(self.text_scale);
 
         //Draw text
         draw!(
             canvas,
             text,
-            Vec2::new(self.rect.x + 20., self.rect.y + 25.),
+            Vec2::new(
+                self.rect.x + 20.,
+                self.rect.y + (self.rect.h - self.text_scale) / 2.
+            ),
             scale
         );
 
@@ -2,19 +2,27 @@
 //! Author: ["Benedikt Brandmaier", "Maximilian Floto", "Leo Schnüll"]
 use crate::backend::rlcolor::RLColor;
 use crate::backend::{
+    error::RLError,
     gamestate::GameState,
     screen::{Screen, StackCommand},
     utils::get_scale,
 };
 use crate::main_menu::button::Button;
-use crate::main_menu::mainmenu::Message::{Exit, NewGame, Resume};
-use crate::RLResult;
+use crate::main_menu::mainmenu::Message::{
+    Exit, Handbook, Language, Load, NewGame, QuitToMenu, Resume, Save,
+};
+use crate::main_menu::slotscreen::SlotScreen;
+use crate::{tr, RLResult};
 
+use crate::backend::options::Options;
 use crate::backend::screen::Popup;
+use crate::backend::settings::Settings;
 use crate::game_core::infoscreen::InfoScreen;
-use crate::languages::german::{BUTTON_TEXT, RESUME_ERROR_STRING};
+use crate::languages::locale;
 use ggez::{graphics, Context};
+use std::io::ErrorKind;
 use std::sync::mpsc::{channel, Receiver, Sender};
+use tracing::warn;
 
 /// is used to define what every button does
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -22,59 +30,117 @@ pub enum Message {
     Exit,
     NewGame,
     Resume,
+    /// Opens the list of save slots to load a game
+    Load,
+    Language,
+    /// Saves the paused game
+    Save,
+    /// Opens the `SettingsMenu` over the paused game
+    Settings,
+    /// Opens the handbook in the paused game
+    Handbook,
+    /// Saves the paused game and returns to the main menu
+    QuitToMenu,
 }
 
 /// Main menu screen of the game with buttons to start a new game, load a game or exit the game.
 #[derive(Debug)]
 pub struct MainMenu {
     buttons: Vec<Button>,
+    sender: Sender<Message>,
     receiver: Receiver<Message>,
     screen_sender: Sender<StackCommand>,
     background_image: Option<graphics::Image>,
+    /// The command line options, which are handed on to every game
+    options: Options,
 }
 
 impl MainMenu {
     /// Create new `MainMenu`
     /// # Arguments
     /// * `screen_sender` - The sender of the `MainMenu` used to send messages to the `ScreenStack`.
+    /// * `options` - The command line options, which are handed on to every game
     /// # Returns
     /// `MainMenu` - Returns a new `MainMenu`.
-    pub(crate) fn new(screen_sender: Sender<StackCommand>) -> MainMenu {
+    pub(crate) fn new(screen_sender: Sender<StackCommand>, options: Options) -> MainMenu {
         let (sender, receiver) = channel();
+        Self {
+            buttons: MainMenu::create_buttons(&sender),
+            sender,
+            receiver,
+            screen_sender,
+            background_image: None,
+            options,
+        }
+    }
 
+    /// Creates the buttons of the `MainMenu` with the texts of the current language
+    /// # Arguments
+    /// * `sender` - The sender used by the buttons to send their `Message`
+    /// # Returns
+    /// `Vec<Button>` - The buttons of the `MainMenu`
+    fn create_buttons(sender: &Sender<Message>) -> Vec<Button> {
         let start_button = Button::new(
-            BUTTON_TEXT[0].to_string(),
+            tr!("menu.resume"),
             Resume,
             sender.clone(),
-            graphics::Rect::new(1322., 350., 450., 120.),
+            graphics::Rect::new(1322., 280., 450., 120.),
             RLColor::GREY,
             RLColor::DARK_GREY,
         );
 
         let new_game_button = Button::new(
-            BUTTON_TEXT[1].to_string(),
+            tr!("menu.new_game"),
             NewGame,
             sender.clone(),
-            graphics::Rect::new(1322., 490., 450., 120.),
+            graphics::Rect::new(1322., 420., 450., 120.),
+            RLColor::GREY,
+            RLColor::DARK_GREY,
+        );
+
+        let load_button = Button::new(
+            tr!("menu.load"),
+            Load,
+            sender.clone(),
+            graphics::Rect::new(1322., 560., 450., 120.),
+            RLColor::GREY,
+            RLColor::DARK_GREY,
+        );
+
+        let language_button = Button::new(
+            locale::language_name(),
+            Language,
+            sender.clone(),
+            graphics::Rect::new(1322., 700., 450., 120.),
             RLColor::GREY,
             RLColor::DARK_GREY,
         );
 
         let exit_button = Button::new(
-            BUTTON_TEXT[2].to_string(),
+            tr!("menu.exit"),
             Exit,
-            sender,
-            graphics::Rect::new(1322., 630., 450., 120.),
+            sender.clone(),
+            graphics::Rect::new(1322., 840., 450., 120.),
             RLColor::GREY,
             RLColor::DARK_GREY,
         );
 
-        Self {
-            buttons: vec![start_button, new_game_button, exit_button],
-            receiver,
-            screen_sender,
-            background_image: None,
-        }
+        vec![
+            start_button,
+            new_game_button,
+            load_button,
+            language_button,
+            exit_button,
+        ]
+    }
+
+    /// Switches to the next language in `LANGUAGES`, stores it in the settings and updates the buttons
+    /// # Returns
+    /// `RLResult` - Fails if the next language could not be loaded or the settings could not be saved
+    fn switch_language(&mut self) -> RLResult {
+        Settings::switch_language()?;
+        self.buttons = MainMenu::create_buttons(&self.sender);
+        Ok(())
     }
 }
 
@@ -103,21 +169,49 @@ impl Screen for MainMenu {
                     GameState::delete_saves()?;
                     let cloned_sender = self.screen_sender.clone();
                     self.screen_sender.send(StackCommand::Push(Box::new(
-                        InfoScreen::new_introscreen(cloned_sender),
+                        InfoScreen::new_introscreen(cloned_sender, self.options.clone()),
                     )))?;
                 }
                 Resume => {
-                    if let Ok(mut gamestate) = GameState::load(false) {
-                        self.screen_sender.send(StackCommand::Push(Box::new({
-                            gamestate.init(ctx)?;
-                            gamestate
-                        })))?;
-                    } else {
-                        self.screen_sender.send(StackCommand::Popup(Popup::warning(
-                            RESUME_ERROR_STRING.to_string(),
-                        )))?;
+                    match GameState::load(false) {
+                        Ok((mut gamestate, restored)) => {
+                            self.screen_sender.send(StackCommand::Push(Box::new({
+                                gamestate.init(ctx)?;
+                                gamestate.set_options(self.options.clone())?;
+                                gamestate
+                            })))?;
+                            if restored {
+                                self.screen_sender.send(StackCommand::Popup(Popup::warning(
+                                    tr!("menu.backup_loaded"),
+                                )))?;
+                            }
+                        }
+                        Err(RLError::IO(e)) if e.kind() == ErrorKind::NotFound => {
+                            self.screen_sender
+                                .send(StackCommand::Popup(Popup::warning(tr!(
+                                    "menu.resume_error"
+                                ))))?;
+                        }
+                        // The save exists, but is broken or of a newer version of the game
+                        Err(e) => {
+                            warn!("Could not load the autosave: {:?}", e);
+                            self.screen_sender
+                                .send(StackCommand::Popup(Popup::warning(tr!(
+                                    "menu.resume_invalid"
+                                ))))?;
+                        }
                     }
                 }
+                Load => {
+                    self.screen_sender
+                        .send(StackCommand::Push(Box::new(SlotScreen::new_load(
+                            self.screen_sender.clone(),
+                            self.options.clone(),
+                        ))))?;
+                }
+                Language => self.switch_language()?,
+                // Only used by the `PauseMenu`
+                Save | Message::Settings | Handbook | QuitToMenu => {}
             }
         }
         Ok(())
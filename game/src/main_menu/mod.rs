@@ -1,2 +1,5 @@
 pub(crate) mod button;
 pub(crate) mod mainmenu;
+pub(crate) mod pausemenu;
+pub(crate) mod settingsmenu;
+pub(crate) mod slotscreen;
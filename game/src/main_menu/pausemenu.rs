@@ -0,0 +1,163 @@
+//! Contains the `PauseMenu` screen, which is drawn over the paused game.
+use crate::backend::constants::SCREEN_RESOLUTION;
+use crate::backend::rlcolor::RLColor;
+use crate::backend::screen::{Screen, StackCommand};
+use crate::backend::utils::{get_draw_params, get_scale};
+use crate::main_menu::button::Button;
+use crate::main_menu::mainmenu::Message::{
+    self, Exit, Handbook, Language, Load, NewGame, QuitToMenu, Resume, Save, Settings,
+};
+use crate::main_menu::settingsmenu::SettingsMenu;
+use crate::{draw, tr, RLResult};
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, DrawMode, Mesh, Rect, Text, TextFragment};
+use ggez::Context;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// The menu shown while the game is paused. The game is drawn below it, but does not run.
+/// Every button except `Resume` and `Settings` closes the menu and sends its `Message` to the game.
+#[derive(Debug)]
+pub(crate) struct PauseMenu {
+    buttons: Vec<Button>,
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    /// Sends the chosen action to the paused game
+    game_sender: Sender<Message>,
+    screen_sender: Sender<StackCommand>,
+    /// Whether the language may have changed in the `SettingsMenu` since the buttons were created
+    outdated_buttons: bool,
+}
+
+impl PauseMenu {
+    /// Creates a new `PauseMenu`
+    /// # Arguments
+    /// * `screen_sender` - The sender used to send messages to the `ScreenStack`
+    /// * `game_sender` - The sender used to send the chosen action to the paused game
+    /// # Returns
+    /// `PauseMenu` - Returns a new `PauseMenu`.
+    pub(crate) fn new(screen_sender: Sender<StackCommand>, game_sender: Sender<Message>) -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            buttons: Self::create_buttons(&sender),
+            sender,
+            receiver,
+            game_sender,
+            screen_sender,
+            outdated_buttons: false,
+        }
+    }
+
+    /// Creates the buttons of the `PauseMenu` with the texts of the current language
+    /// # Arguments
+    /// * `sender` - The sender used by the buttons to send their `Message`
+    /// # Returns
+    /// `Vec<Button>` - The buttons of the `PauseMenu`
+    fn create_buttons(sender: &Sender<Message>) -> Vec<Button> {
+        [
+            (tr!("menu.resume"), Resume),
+            (tr!("menu.save"), Save),
+            (tr!("menu.settings"), Settings),
+            (tr!("menu.handbook"), Handbook),
+            (tr!("menu.quit_to_menu"), QuitToMenu),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (text, message))| {
+            Button::new(
+                text,
+                message,
+                sender.clone(),
+                Rect::new(
+                    (SCREEN_RESOLUTION.0 - 600.) / 2.,
+                    230. + 140. * i as f32,
+                    600.,
+                    120.,
+                ),
+                RLColor::GREY,
+                RLColor::DARK_GREY,
+            )
+        })
+        .collect()
+    }
+}
+
+impl Screen for PauseMenu {
+    /// Handles the buttons of the menu, Escape resumes the game.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// # Returns
+    /// `RLResult` - Returns an `RLResult`.
+    fn update(&mut self, ctx: &mut Context) -> RLResult {
+        if self.outdated_buttons {
+            self.buttons = Self::create_buttons(&self.sender);
+            self.outdated_buttons = false;
+        }
+        let scale = get_scale(ctx);
+        self.buttons.iter_mut().for_each(|btn| {
+            btn.action(ctx, scale);
+        });
+        if ctx
+            .keyboard
+            .is_key_just_pressed(ggez::winit::event::VirtualKeyCode::Escape)
+        {
+            self.sender.send(Resume)?;
+        }
+        if let Ok(msg) = self.receiver.try_recv() {
+            match msg {
+                Resume => self.screen_sender.send(StackCommand::Pop)?,
+                Settings => {
+                    let settings = SettingsMenu::new(self.screen_sender.clone());
+                    self.screen_sender
+                        .send(StackCommand::Push(Box::new(settings)))?;
+                    // The menu is not updated again before the `SettingsMenu` is closed
+                    self.outdated_buttons = true;
+                }
+                Save | Handbook | QuitToMenu => {
+                    self.game_sender.send(msg)?;
+                    self.screen_sender.send(StackCommand::Pop)?;
+                }
+                // Only used by the `MainMenu`
+                Exit | NewGame | Load | Language => {}
+            }
+        }
+        Ok(())
+    }
+    /// Darkens the game below and draws the buttons of the menu.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// # Returns
+    /// `RLResult` - Returns an `RLResult`.
+    fn draw(&self, ctx: &mut Context) -> RLResult {
+        let scale = get_scale(ctx);
+        let mut canvas = Canvas::from_frame(ctx, None);
+        let shadow = Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., SCREEN_RESOLUTION.0, SCREEN_RESOLUTION.1),
+            RLColor::SHADOW,
+        )?;
+        draw!(canvas, &shadow, Vec2::new(0., 0.), scale);
+        let mut title = Text::new(TextFragment::new(tr!("hud.paused")).color(RLColor::GREY));
+        title.set_scale(90.);
+        let width = title.measure(ctx)?.x;
+        draw!(
+            canvas,
+            &title,
+            Vec2::new((SCREEN_RESOLUTION.0 - width) / 2., 100.),
+            scale
+        );
+        for btn in &self.buttons {
+            btn.draw_button(ctx, &mut canvas)?;
+        }
+        canvas.finish(ctx)?;
+        Ok(())
+    }
+
+    fn set_sender(&mut self, sender: Sender<StackCommand>) {
+        self.screen_sender = sender;
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
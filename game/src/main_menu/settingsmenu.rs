@@ -0,0 +1,146 @@
+//! Contains the `SettingsMenu` screen, which is opened from the pause menu.
+use crate::backend::constants::SCREEN_RESOLUTION;
+use crate::backend::rlcolor::RLColor;
+use crate::backend::screen::{Screen, StackCommand};
+use crate::backend::settings::Settings;
+use crate::backend::utils::{get_draw_params, get_scale};
+use crate::languages::locale;
+use crate::main_menu::button::Button;
+use crate::{draw, tr, RLResult};
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Rect, Text, TextFragment};
+use ggez::winit::event::VirtualKeyCode;
+use ggez::Context;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Is used to define what every button of the `SettingsMenu` does
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SettingsMessage {
+    /// Switches to the next language
+    Language,
+    /// Switches to the next autosave interval
+    Autosave,
+    Back,
+}
+
+/// Shows the settings while the game is paused. Every change is stored in the settings file right away.
+#[derive(Debug)]
+pub(crate) struct SettingsMenu {
+    buttons: Vec<Button<SettingsMessage>>,
+    sender: Sender<SettingsMessage>,
+    receiver: Receiver<SettingsMessage>,
+    screen_sender: Sender<StackCommand>,
+}
+
+impl SettingsMenu {
+    /// Creates a new `SettingsMenu`
+    /// # Arguments
+    /// * `screen_sender` - The sender used to send messages to the `ScreenStack`
+    /// # Returns
+    /// `SettingsMenu` - Returns a new `SettingsMenu`.
+    pub(crate) fn new(screen_sender: Sender<StackCommand>) -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            buttons: Self::create_buttons(&sender),
+            sender,
+            receiver,
+            screen_sender,
+        }
+    }
+
+    /// Creates the buttons of the `SettingsMenu` with the current settings
+    /// # Arguments
+    /// * `sender` - The sender used by the buttons to send their `SettingsMessage`
+    /// # Returns
+    /// `Vec<Button<SettingsMessage>>` - The buttons of the `SettingsMenu`
+    fn create_buttons(sender: &Sender<SettingsMessage>) -> Vec<Button<SettingsMessage>> {
+        let autosave = match Settings::load().autosave_interval {
+            0 => tr!("settings.autosave_off"),
+            minutes => tr!("settings.autosave", minutes = minutes),
+        };
+        [
+            (
+                tr!("settings.language", language = locale::language_name()),
+                SettingsMessage::Language,
+            ),
+            (autosave, SettingsMessage::Autosave),
+            (tr!("settings.back"), SettingsMessage::Back),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (text, message))| {
+            let mut button = Button::new(
+                text,
+                message,
+                sender.clone(),
+                Rect::new(
+                    (SCREEN_RESOLUTION.0 - 1000.) / 2.,
+                    300. + 140. * i as f32,
+                    1000.,
+                    120.,
+                ),
+                RLColor::GREY,
+                RLColor::DARK_GREY,
+            );
+            button.text_scale = 50.;
+            button
+        })
+        .collect()
+    }
+}
+
+impl Screen for SettingsMenu {
+    /// Handles the buttons of the menu, Escape returns to the pause menu.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// # Returns
+    /// `RLResult` - Returns an `RLResult`.
+    fn update(&mut self, ctx: &mut Context) -> RLResult {
+        let scale = get_scale(ctx);
+        self.buttons.iter_mut().for_each(|btn| {
+            btn.action(ctx, scale);
+        });
+        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::Escape) {
+            self.sender.send(SettingsMessage::Back)?;
+        }
+        if let Ok(message) = self.receiver.try_recv() {
+            match message {
+                SettingsMessage::Language => Settings::switch_language()?,
+                SettingsMessage::Autosave => Settings::switch_autosave_interval()?,
+                SettingsMessage::Back => {
+                    self.screen_sender.send(StackCommand::Pop)?;
+                    return Ok(());
+                }
+            }
+            self.buttons = Self::create_buttons(&self.sender);
+        }
+        Ok(())
+    }
+    /// Draws the buttons of the menu, which hide the pause menu below.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// # Returns
+    /// `RLResult` - Returns an `RLResult`.
+    fn draw(&self, ctx: &mut Context) -> RLResult {
+        let scale = get_scale(ctx);
+        let mut canvas = Canvas::from_frame(ctx, RLColor::DARK_BLUE);
+        let mut title = Text::new(TextFragment::new(tr!("settings.title")).color(RLColor::GREY));
+        title.set_scale(90.);
+        let width = title.measure(ctx)?.x;
+        draw!(
+            canvas,
+            &title,
+            Vec2::new((SCREEN_RESOLUTION.0 - width) / 2., 100.),
+            scale
+        );
+        for btn in &self.buttons {
+            btn.draw_button(ctx, &mut canvas)?;
+        }
+        canvas.finish(ctx)?;
+        Ok(())
+    }
+
+    fn set_sender(&mut self, sender: Sender<StackCommand>) {
+        self.screen_sender = sender;
+    }
+}
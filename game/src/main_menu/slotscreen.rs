@@ -0,0 +1,366 @@
+//! Contains the `SlotScreen`, which lists the save slots to load, save, rename and delete games.
+use crate::backend::constants::{SAVE_SLOTS_PATH, SCREEN_RESOLUTION};
+use crate::backend::gamestate::GameState;
+use crate::backend::options::Options;
+use crate::backend::rlcolor::RLColor;
+use crate::backend::save_slots::{delete_slot, list_slots, rename_slot, SlotFile, SlotMetadata};
+use crate::backend::screen::{Popup, Screen, StackCommand};
+use crate::backend::utils::{format_play_time, get_draw_params, get_scale};
+use crate::main_menu::button::Button;
+use crate::{draw, tr, RLResult};
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Color, DrawMode, Mesh, Rect, Text, TextFragment};
+use ggez::winit::event::VirtualKeyCode;
+use ggez::Context;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use tracing::warn;
+
+/// The y position of the first slot
+const ROW_Y: f32 = 200.;
+/// The space between two slots
+const ROW_HEIGHT: f32 = 150.;
+/// The most characters the name of a save can have
+const MAX_NAME_LENGTH: usize = 24;
+
+/// Is used to define what every button of the `SlotScreen` does
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SlotMessage {
+    /// Loads the game of the slot or saves the paused game into it
+    Use(usize),
+    Rename(usize),
+    Delete(usize),
+    Back,
+}
+
+/// Whether the `SlotScreen` loads a game or saves the paused game
+#[derive(Debug)]
+enum SlotMode {
+    Load,
+    /// Contains the paused game, the slot it is saved into decides its name
+    Save(SlotFile),
+}
+
+/// Lists the save slots with the name, milestone, play time, date and description of their games.
+/// Opened from the main menu it loads games, opened from the pause menu it saves the paused game.
+/// Overwriting and deleting a game has to be confirmed by clicking the button a second time.
+#[derive(Debug)]
+pub(crate) struct SlotScreen {
+    mode: SlotMode,
+    slots: Vec<Option<SlotMetadata>>,
+    buttons: Vec<Button<SlotMessage>>,
+    sender: Sender<SlotMessage>,
+    receiver: Receiver<SlotMessage>,
+    screen_sender: Sender<StackCommand>,
+    /// The action which waits for a second click to be confirmed
+    confirm: Option<SlotMessage>,
+    /// The slot which is renamed and the name typed so far
+    renaming: Option<(usize, String)>,
+    /// The command line options, which are handed on to the loaded game
+    options: Options,
+}
+
+impl SlotScreen {
+    /// Creates a `SlotScreen` which loads the game of a slot
+    /// # Arguments
+    /// * `screen_sender` - The sender used to send messages to the `ScreenStack`
+    /// * `options` - The command line options, which are handed on to the loaded game
+    pub(crate) fn new_load(screen_sender: Sender<StackCommand>, options: Options) -> Self {
+        Self::new(SlotMode::Load, screen_sender, options)
+    }
+
+    /// Creates a `SlotScreen` which saves the paused game into a slot
+    /// # Arguments
+    /// * `screen_sender` - The sender used to send messages to the `ScreenStack`
+    /// * `game` - The paused game
+    pub(crate) fn new_save(screen_sender: Sender<StackCommand>, game: SlotFile) -> Self {
+        Self::new(SlotMode::Save(game), screen_sender, Options::default())
+    }
+
+    fn new(mode: SlotMode, screen_sender: Sender<StackCommand>, options: Options) -> Self {
+        let (sender, receiver) = channel();
+        let mut screen = Self {
+            mode,
+            slots: vec![],
+            buttons: vec![],
+            sender,
+            receiver,
+            screen_sender,
+            confirm: None,
+            renaming: None,
+            options,
+        };
+        screen.refresh();
+        screen
+    }
+
+    /// Reads the slots again and recreates the buttons
+    fn refresh(&mut self) {
+        self.slots = list_slots(SAVE_SLOTS_PATH);
+        self.buttons = self.create_buttons();
+    }
+
+    /// Creates the buttons of every slot and the back button
+    /// # Returns
+    /// `Vec<Button<SlotMessage>>` - The buttons of the `SlotScreen`
+    fn create_buttons(&self) -> Vec<Button<SlotMessage>> {
+        let button = |text: String, message: SlotMessage, rect: Rect| {
+            let text = if self.confirm == Some(message) {
+                tr!("slots.confirm")
+            } else {
+                text
+            };
+            let mut button = Button::new(
+                text,
+                message,
+                self.sender.clone(),
+                rect,
+                RLColor::GREY,
+                RLColor::DARK_GREY,
+            );
+            button.text_scale = 40.;
+            button
+        };
+        let mut buttons = vec![];
+        for (slot, metadata) in self.slots.iter().enumerate() {
+            let y = ROW_Y + ROW_HEIGHT * slot as f32;
+            match (&self.mode, metadata) {
+                (SlotMode::Load, Some(_)) => buttons.push(button(
+                    tr!("slots.load"),
+                    SlotMessage::Use(slot),
+                    Rect::new(1100., y, 220., 80.),
+                )),
+                (SlotMode::Save(_), _) => buttons.push(button(
+                    tr!("slots.save"),
+                    SlotMessage::Use(slot),
+                    Rect::new(1100., y, 220., 80.),
+                )),
+                (SlotMode::Load, None) => {}
+            }
+            if metadata.is_some() {
+                buttons.push(button(
+                    tr!("slots.rename"),
+                    SlotMessage::Rename(slot),
+                    Rect::new(1340., y, 260., 80.),
+                ));
+                buttons.push(button(
+                    tr!("slots.delete"),
+                    SlotMessage::Delete(slot),
+                    Rect::new(1620., y, 220., 80.),
+                ));
+            }
+        }
+        buttons.push(button(
+            tr!("slots.back"),
+            SlotMessage::Back,
+            Rect::new((SCREEN_RESOLUTION.0 - 300.) / 2., 960., 300., 80.),
+        ));
+        buttons
+    }
+
+    /// Handles a clicked button. Overwriting and deleting a game only happen on the second click.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// * `message` - The message of the clicked button
+    fn handle(&mut self, ctx: &mut Context, message: SlotMessage) -> RLResult {
+        let overwrites =
+            |slot: usize| matches!(self.mode, SlotMode::Save(_)) && self.slots[slot].is_some();
+        let needs_confirmation = match message {
+            SlotMessage::Use(slot) => overwrites(slot),
+            SlotMessage::Delete(_) => true,
+            SlotMessage::Rename(_) | SlotMessage::Back => false,
+        };
+        if needs_confirmation && self.confirm != Some(message) {
+            self.confirm = Some(message);
+            self.buttons = self.create_buttons();
+            return Ok(());
+        }
+        self.confirm = None;
+        match message {
+            SlotMessage::Use(slot) => self.use_slot(ctx, slot)?,
+            SlotMessage::Rename(slot) => {
+                let name = self.slots[slot]
+                    .as_ref()
+                    .map(|metadata| metadata.name.clone())
+                    .unwrap_or_default();
+                self.renaming = Some((slot, name));
+            }
+            SlotMessage::Delete(slot) => delete_slot(SAVE_SLOTS_PATH, slot)?,
+            SlotMessage::Back => self.screen_sender.send(StackCommand::Pop)?,
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    /// Loads the game of a slot or saves the paused game into it
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// * `slot` - The index of the slot
+    fn use_slot(&mut self, ctx: &mut Context, slot: usize) -> RLResult {
+        match &self.mode {
+            SlotMode::Load => {
+                match SlotFile::read(SAVE_SLOTS_PATH, slot).and_then(|file| file.game()) {
+                    Ok(mut gamestate) => {
+                        // The loaded game replaces the current game
+                        GameState::delete_saves()?;
+                        gamestate.init(ctx)?;
+                        gamestate.set_options(self.options.clone())?;
+                        // Dying returns the player to the loaded game
+                        gamestate.save(true)?;
+                        self.screen_sender.send(StackCommand::Pop)?;
+                        self.screen_sender
+                            .send(StackCommand::Push(Box::new(gamestate)))?;
+                    }
+                    Err(e) => {
+                        warn!("Could not load slot {}: {:?}", slot + 1, e);
+                        let popup = Popup::warning(tr!("slots.load_error"));
+                        self.screen_sender.send(StackCommand::Popup(popup))?;
+                    }
+                }
+            }
+            SlotMode::Save(file) => {
+                let mut file = file.clone();
+                file.metadata.name = self.slots[slot].as_ref().map_or_else(
+                    || tr!("slots.default_name", number = slot + 1),
+                    |metadata| metadata.name.clone(),
+                );
+                file.write(SAVE_SLOTS_PATH, slot)?;
+                let popup = Popup::info(tr!("menu.saved"));
+                self.screen_sender.send(StackCommand::Popup(popup))?;
+                self.screen_sender.send(StackCommand::Pop)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles the keys while a slot is renamed. Enter stores the new name, Escape cancels.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    fn update_renaming(&mut self, ctx: &Context) -> RLResult {
+        let keyboard = &ctx.keyboard;
+        if keyboard.is_key_just_pressed(VirtualKeyCode::Escape) {
+            self.renaming = None;
+        } else if keyboard.is_key_just_pressed(VirtualKeyCode::Return) {
+            if let Some((slot, name)) = self.renaming.take() {
+                if !name.trim().is_empty() {
+                    rename_slot(SAVE_SLOTS_PATH, slot, name.trim().to_string())?;
+                }
+            }
+            self.refresh();
+        } else if keyboard.is_key_just_pressed(VirtualKeyCode::Back) {
+            if let Some((_, name)) = &mut self.renaming {
+                name.pop();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Screen for SlotScreen {
+    /// Handles the buttons and the renaming of a slot, Escape returns to the previous screen.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// # Returns
+    /// `RLResult` - Returns an `RLResult`.
+    fn update(&mut self, ctx: &mut Context) -> RLResult {
+        if self.renaming.is_some() {
+            // The buttons are disabled while a name is typed
+            self.receiver.try_iter().for_each(drop);
+            return self.update_renaming(ctx);
+        }
+        let scale = get_scale(ctx);
+        self.buttons.iter_mut().for_each(|btn| {
+            btn.action(ctx, scale);
+        });
+        if ctx.keyboard.is_key_just_pressed(VirtualKeyCode::Escape) {
+            self.sender.send(SlotMessage::Back)?;
+        }
+        if let Ok(message) = self.receiver.try_recv() {
+            self.handle(ctx, message)?;
+        }
+        Ok(())
+    }
+    /// Draws the slots with their metadata and the buttons.
+    /// # Arguments
+    /// * `ctx` - The ggez context
+    /// # Returns
+    /// `RLResult` - Returns an `RLResult`.
+    fn draw(&self, ctx: &mut Context) -> RLResult {
+        let scale = get_scale(ctx);
+        let (mut canvas, title) = match self.mode {
+            SlotMode::Load => (
+                Canvas::from_frame(ctx, RLColor::DARK_BLUE),
+                tr!("slots.title_load"),
+            ),
+            SlotMode::Save(_) => {
+                let mut canvas = Canvas::from_frame(ctx, None);
+                let shadow = Mesh::new_rectangle(
+                    ctx,
+                    DrawMode::fill(),
+                    Rect::new(0., 0., SCREEN_RESOLUTION.0, SCREEN_RESOLUTION.1),
+                    RLColor::SHADOW,
+                )?;
+                draw!(canvas, &shadow, Vec2::new(0., 0.), scale);
+                (canvas, tr!("slots.title_save"))
+            }
+        };
+        let mut title = Text::new(TextFragment::new(title).color(Color::WHITE));
+        title.set_scale(70.);
+        let width = title.measure(ctx)?.x;
+        draw!(
+            canvas,
+            &title,
+            Vec2::new((SCREEN_RESOLUTION.0 - width) / 2., 60.),
+            scale
+        );
+        for (slot, metadata) in self.slots.iter().enumerate() {
+            let (name, details, description) = match (&self.renaming, metadata) {
+                (Some((renaming, name)), _) if *renaming == slot => {
+                    (format!("{name}_"), tr!("slots.rename_hint"), String::new())
+                }
+                (_, Some(metadata)) => (
+                    metadata.name.clone(),
+                    tr!(
+                        "slots.details",
+                        milestone = metadata.milestone,
+                        time = format_play_time(metadata.play_time),
+                        date = metadata.saved_at
+                    ),
+                    metadata.description.clone(),
+                ),
+                (_, None) => (tr!("slots.empty"), String::new(), String::new()),
+            };
+            let y = ROW_Y + ROW_HEIGHT * slot as f32;
+            let mut name = Text::new(TextFragment::new(name).color(Color::WHITE));
+            name.set_scale(40.);
+            draw!(canvas, &name, Vec2::new(150., y), scale);
+            let mut details = Text::new(TextFragment::new(details).color(RLColor::GREY));
+            details.set_scale(28.);
+            draw!(canvas, &details, Vec2::new(150., y + 50.), scale);
+            let mut description = Text::new(TextFragment::new(description).color(RLColor::GREY));
+            description.set_scale(28.);
+            draw!(canvas, &description, Vec2::new(150., y + 85.), scale);
+        }
+        for btn in &self.buttons {
+            btn.draw_button(ctx, &mut canvas)?;
+        }
+        canvas.finish(ctx)?;
+        Ok(())
+    }
+
+    fn set_sender(&mut self, sender: Sender<StackCommand>) {
+        self.screen_sender = sender;
+    }
+
+    fn is_overlay(&self) -> bool {
+        matches!(self.mode, SlotMode::Save(_))
+    }
+
+    fn text_input(&mut self, character: char) -> RLResult {
+        if let Some((_, name)) = &mut self.renaming {
+            if !character.is_control() && name.chars().count() < MAX_NAME_LENGTH {
+                name.push(character);
+            }
+        }
+        Ok(())
+    }
+}